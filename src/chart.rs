@@ -0,0 +1,38 @@
+const CELL_SIZE: u32 = 16;
+const BORDER: u32 = 1;
+const AVAILABLE: [u8; 3] = [0x3b, 0xa5, 0x5d];
+const UNAVAILABLE: [u8; 3] = [0x4f, 0x54, 0x5c];
+const GRID: [u8; 3] = [0x2b, 0x2d, 0x31];
+
+/// Renders an availability matrix as a PNG, with one row per user and one column per date
+pub fn render_heatmap(rows: &[Vec<bool>]) -> Result<Vec<u8>, png::EncodingError> {
+    let columns = rows.first().map_or(0, |r| r.len()) as u32;
+    let width = columns * (CELL_SIZE + BORDER) + BORDER;
+    let height = rows.len() as u32 * (CELL_SIZE + BORDER) + BORDER;
+
+    let mut pixels = Vec::with_capacity((width * height * 3) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let on_grid = x % (CELL_SIZE + BORDER) < BORDER || y % (CELL_SIZE + BORDER) < BORDER;
+            let color = if on_grid {
+                GRID
+            } else {
+                let row = (y / (CELL_SIZE + BORDER)) as usize;
+                let column = (x / (CELL_SIZE + BORDER)) as usize;
+                if rows[row][column] {
+                    AVAILABLE
+                } else {
+                    UNAVAILABLE
+                }
+            };
+            pixels.extend_from_slice(&color);
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)?;
+    Ok(data)
+}
@@ -0,0 +1,81 @@
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io;
+use std::sync::OnceLock;
+
+// Marks an encrypted file, which is followed by the nonce and then the encrypted JSON. Plain JSON
+// files always start with `{`, so the two can't be confused.
+const MAGIC: &[u8] = b"SCHEDULER-ENCRYPTED-1\n";
+
+static KEY: OnceLock<Option<LessSafeKey>> = OnceLock::new();
+
+/// Sets the key that saved data is encrypted with, given as a base64-encoded 32-byte key. Data is
+/// saved as plain JSON if there isn't one. Must be called before any data is read or written.
+pub fn init(key: Option<&str>) -> Result<(), String> {
+    let key = match key {
+        Some(key) => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(key.trim())
+                .map_err(|e| format!("DATA_KEY isn't valid base64 ({})", e))?;
+            let key = UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+                .map_err(|_| "DATA_KEY must be 32 bytes long".to_owned())?;
+            Some(LessSafeKey::new(key))
+        }
+        None => None,
+    };
+    KEY.set(key)
+        .map_err(|_| "Encryption key was already set".to_owned())
+}
+
+fn key() -> Option<&'static LessSafeKey> {
+    KEY.get().and_then(Option::as_ref)
+}
+
+/// Encrypts serialized data to be saved, if a key is set
+pub fn encrypt(mut data: Vec<u8>) -> io::Result<Vec<u8>> {
+    let key = match key() {
+        Some(key) => key,
+        None => return Ok(data),
+    };
+    let mut nonce = [0; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| io::Error::other("Cannot generate nonce"))?;
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| io::Error::other("Cannot encrypt data"))?;
+    Ok([MAGIC, &nonce, &data].concat())
+}
+
+/// Decrypts saved data if it was encrypted. Plain JSON is returned unchanged even when a key is
+/// set, so that existing files can still be read after encryption is enabled.
+pub fn decrypt(data: Vec<u8>) -> io::Result<Vec<u8>> {
+    let encrypted = match data.strip_prefix(MAGIC) {
+        Some(encrypted) => encrypted,
+        None => return Ok(data),
+    };
+    let key = key().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "File is encrypted, but DATA_KEY isn't set",
+        )
+    })?;
+    if encrypted.len() < NONCE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Encrypted file is truncated",
+        ));
+    }
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).unwrap();
+    let mut ciphertext = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Cannot decrypt file; DATA_KEY may be wrong or the file corrupted",
+            )
+        })?;
+    Ok(plaintext.to_vec())
+}
@@ -0,0 +1,81 @@
+use crate::strings::Text;
+
+use std::fmt;
+
+/// Reasons a scheduler operation can fail. The `Display` text is suitable for showing to the
+/// user who attempted the operation.
+#[derive(Debug)]
+pub enum SchedulerError {
+    /// The operation is limited to the scheduler owner
+    NotOwner,
+    Closed,
+    Locked,
+    /// The maximum number of responders has been reached
+    Full,
+    /// Only users who have already responded may change their response
+    ClosedToNew,
+    /// The user opened another editing session after this one
+    Superseded,
+    /// The user submitted another response too soon after their last one
+    TooFast,
+    /// The channel already has as many pinned messages as Discord allows
+    PinLimit,
+    /// Options given when creating a scheduler don't produce a usable poll
+    InvalidOptions(String),
+    /// A Discord request failed, e.g. because the message was deleted. Boxed because
+    /// `serenity::Error` is much larger than the other variants.
+    Discord(Box<serenity::Error>),
+    /// The scheduler couldn't be saved to disk
+    Persistence(std::io::Error),
+}
+
+impl SchedulerError {
+    /// Text shown to the user in the given Discord locale, falling back to English. Messages about
+    /// invalid options are only given in English.
+    pub fn localized(&self, locale: Option<&str>) -> String {
+        let text = match self {
+            SchedulerError::NotOwner => Text::NotOwner,
+            SchedulerError::Closed => Text::Closed,
+            SchedulerError::Locked => Text::Locked,
+            SchedulerError::Full => Text::Full,
+            SchedulerError::ClosedToNew => Text::ClosedToNewError,
+            SchedulerError::Superseded => Text::Superseded,
+            SchedulerError::TooFast => Text::TooFast,
+            SchedulerError::PinLimit => Text::PinLimit,
+            SchedulerError::InvalidOptions(msg) => return msg.clone(),
+            SchedulerError::Discord(e) => {
+                return format!("{} ({})", Text::DiscordFailed.get(locale), e)
+            }
+            SchedulerError::Persistence(e) => {
+                return format!("{} ({})", Text::CannotSave.get(locale), e)
+            }
+        };
+        text.get(locale).to_owned()
+    }
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.localized(None))
+    }
+}
+
+impl std::error::Error for SchedulerError {}
+
+impl From<serenity::Error> for SchedulerError {
+    fn from(e: serenity::Error) -> Self {
+        SchedulerError::Discord(Box::new(e))
+    }
+}
+
+impl From<std::io::Error> for SchedulerError {
+    fn from(e: std::io::Error) -> Self {
+        SchedulerError::Persistence(e)
+    }
+}
+
+impl From<serde_json::Error> for SchedulerError {
+    fn from(e: serde_json::Error) -> Self {
+        SchedulerError::Persistence(e.into())
+    }
+}
@@ -1,39 +1,82 @@
+#[cfg(feature = "heatmap")]
+mod chart;
+mod encryption;
+mod error;
 mod message_shim;
+mod notify;
+mod pending;
+mod profile;
+mod registry;
 mod scheduler;
-use crate::scheduler::{ResponseType, Scheduler};
+mod session;
+mod strings;
+use crate::error::SchedulerError;
+use crate::notify::{Notification, Notifier, Recipient};
+use crate::pending::{PendingPost, PendingPosts};
+use crate::profile::{Profile, Profiles};
+use crate::registry::Registry;
+use crate::scheduler::{
+    HighlightStyle, ResponseType, ResultsVisibility, RoleLossPolicy, Scheduler, TieBreak,
+    CONFIRM_TIMEOUT, MAX_COVERAGE_DATES, MAX_INTERVAL_WEEKS, MAX_NOTES, MAX_SKIP_WEEKS, MAX_TITLE,
+};
+#[cfg(feature = "dev-tools")]
+use crate::session::ScriptedSession;
+use crate::session::{CommandSession, ComponentSession, ModalSession, ResponseSession};
 
-use chrono::Weekday;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
 use clap::Parser;
 use dotenv::dotenv;
-use lockfree::map::{Map, ReadGuard};
-use log::{error, info};
+use log::{error, info, warn};
 use serenity::async_trait;
+use serenity::builder::{CreateApplicationCommandOption, CreateComponents};
 use serenity::client::{Context, EventHandler};
+use serenity::http::Http;
 use serenity::json::Value;
 use serenity::model::application::command::{Command, CommandOptionType};
+use serenity::model::application::component::ButtonStyle;
 use serenity::model::application::interaction::{
     application_command::ApplicationCommandInteraction,
     message_component::MessageComponentInteraction, Interaction, InteractionResponseType,
 };
-use serenity::model::channel::Message;
+use serenity::model::channel::{AttachmentType, Channel, GuildChannel, Message, Reaction};
 use serenity::model::gateway::Ready;
-use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId};
+use serenity::model::guild::Member;
+use serenity::model::id::{AttachmentId, ChannelId, GuildId, MessageId, RoleId, UserId};
+use serenity::model::user::User;
 use serenity::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::env;
-use std::fs::File;
 use std::panic;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
 const DATA_DIR: &str = "data";
+// Longest message Discord accepts
+const MESSAGE_LIMIT: usize = 2000;
 const MAX_DATES: usize = 25; // limit for select menu
+const SUMMARY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+// Checked more often than daily so that dates are blacked out soon after midnight
+const PAST_DATES_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+// Default age in days of the responses nudged by `/schedule nudge`
+const STALE_RESPONSE_DAYS: i64 = 14;
+// Bumped when exported schedulers can no longer be imported with defaults for the new fields
+const EXPORT_VERSION: u64 = 1;
+// Largest file accepted by `/schedule import`, checked before downloading it
+const MAX_IMPORT_SIZE: u64 = 4 * 1024 * 1024;
 
-#[derive(Default)]
 struct Handler {
     refresh: bool,
-    schedulers: Map<MessageId, Scheduler>,
-    reposts: Map<MessageId, MessageId>,
+    /// Whether member join, leave and update events are received
+    member_events: bool,
+    // Shared with the daily summary task
+    registry: Arc<Registry>,
+    notifier: Arc<Notifier>,
+    profiles: Arc<Profiles>,
+    pending: Arc<PendingPosts>,
+    /// User allowed to run the developer commands
+    #[cfg(feature = "dev-tools")]
+    dev_user: Option<UserId>,
     startup_done: tokio::sync::OnceCell<()>,
 }
 
@@ -47,6 +90,193 @@ async fn send_error(ctx: &Context, command: &ApplicationCommandInteraction, msg:
         .expect("Cannot send error response");
 }
 
+/// Whether a channel is in `guild_id`, including threads and forum posts, which the cache only
+/// lists under their guild. The cache only has active threads, so channels it doesn't know, e.g.
+/// archived threads, are looked up.
+async fn in_guild(ctx: &Context, channel_id: ChannelId, guild_id: GuildId) -> bool {
+    if let Some(channel) = ctx.cache.guild_channel(channel_id) {
+        return channel.guild_id == guild_id;
+    }
+    let has_thread = |guild| {
+        ctx.cache
+            .guild_field(guild, |g| g.threads.iter().any(|t| t.id == channel_id))
+            .unwrap_or(false)
+    };
+    if let Some(guild) = ctx.cache.guilds().into_iter().find(|g| has_thread(*g)) {
+        return guild == guild_id;
+    }
+    match channel_id.to_channel(ctx).await {
+        Ok(Channel::Guild(channel)) => channel.guild_id == guild_id,
+        _ => false,
+    }
+}
+
+/// Parses a list of weekdays joined by `+`, e.g. `Sat+Sun`
+fn parse_days(value: &Value) -> HashSet<Weekday> {
+    value
+        .as_str()
+        .expect("Days has incorrect type")
+        .split('+')
+        .map(|d| Weekday::from_str(d).expect("Cannot parse day"))
+        .collect()
+}
+
+/// Adds the options of `/schedule create` that determine which dates are included, which
+/// `/schedule preview` shares
+fn add_date_options(o: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    o.create_sub_option(|o| {
+        o.name("limit")
+            .description("number of dates to include, rounded down to whole weeks")
+            .kind(CommandOptionType::Integer)
+            .min_int_value(1)
+            .max_int_value(MAX_DATES)
+    })
+    .create_sub_option(|o| {
+        o.name("weeks")
+            .description("number of weeks to include, instead of limit (dates = weeks x days)")
+            .kind(CommandOptionType::Integer)
+            .min_int_value(1)
+            .max_int_value(MAX_DATES)
+    })
+    .create_sub_option(|o| {
+        o.name("skip")
+            .description("weeks before start")
+            .kind(CommandOptionType::Integer)
+            .min_int_value(0)
+            .max_int_value(MAX_SKIP_WEEKS)
+    })
+    .create_sub_option(|o| {
+        o.name("start_date")
+            .description("first date to include, as YYYY-MM-DD (overrides skip)")
+            .kind(CommandOptionType::String)
+    })
+    .create_sub_option(|o| {
+        o.name("days")
+            .description("weekdays to include")
+            .kind(CommandOptionType::String)
+            .add_string_choice("Saturday + Sunday", "Sat+Sun")
+            .add_string_choice("Sunday", "Sun")
+            .add_string_choice("Monday", "Mon")
+            .add_string_choice("Tuesday", "Tue")
+            .add_string_choice("Wednesday", "Wed")
+            .add_string_choice("Thursday", "Thu")
+            .add_string_choice("Friday", "Fri")
+            .add_string_choice("Saturday", "Sat")
+    })
+    .create_sub_option(|o| {
+        o.name("interval_weeks")
+            .description("include every Nth week, e.g. 2 for every other week")
+            .kind(CommandOptionType::Integer)
+            .min_int_value(1)
+            .max_int_value(MAX_INTERVAL_WEEKS)
+    })
+}
+
+/// Options of `/schedule create` that determine which dates are included
+struct DateOptions {
+    limit: i64,
+    skip: Option<i64>,
+    start_date: Option<NaiveDate>,
+    days: HashSet<Weekday>,
+    interval_weeks: Option<i64>,
+}
+
+/// Reads the date options of a command, shared by `/schedule create` and `/schedule preview`.
+/// Returns the message to show if they are invalid.
+fn parse_date_options(options: &HashMap<&str, &Value>) -> Result<DateOptions, String> {
+    if options.contains_key("weeks") && options.contains_key("limit") {
+        return Err("Use either weeks or limit, not both".to_owned());
+    }
+    let limit = match options.get("limit") {
+        Some(limit) => limit.as_i64().expect("Limit has incorrect type"),
+        None => MAX_DATES as i64,
+    };
+    let days = options
+        .get("days")
+        .map(|v| parse_days(v))
+        .unwrap_or_else(|| HashSet::from([Weekday::Sat, Weekday::Sun]));
+    let limit = match options.get("weeks") {
+        Some(weeks) => {
+            let weeks = weeks.as_i64().expect("Weeks has incorrect type");
+            if weeks <= 0 {
+                return Err("Weeks must be at least 1".to_owned());
+            }
+            let limit = weeks * days.len() as i64;
+            if limit > MAX_DATES as i64 {
+                return Err(format!(
+                    "Too many weeks; at most {} dates can be included",
+                    MAX_DATES
+                ));
+            }
+            limit
+        }
+        None => limit,
+    };
+    let interval_weeks = options
+        .get("interval_weeks")
+        .map(|v| v.as_i64().expect("Interval has incorrect type"));
+    let skip = options
+        .get("skip")
+        .map(|v| v.as_i64().expect("Skip has incorrect type"));
+    let start_date = match options.get("start_date") {
+        Some(v) => {
+            let today = Local::now().date_naive();
+            match NaiveDate::parse_from_str(
+                v.as_str().expect("Start date has incorrect type"),
+                "%Y-%m-%d",
+            ) {
+                Ok(date) if date >= today => Some(date),
+                Ok(_) => return Err("Start date can't be in the past".to_owned()),
+                Err(_) => return Err("Start date must be in YYYY-MM-DD format".to_owned()),
+            }
+        }
+        None => None,
+    };
+    Ok(DateOptions {
+        limit,
+        skip,
+        start_date,
+        days,
+        interval_weeks,
+    })
+}
+
+/// Joins `items` with commas, replacing those that don't fit in `budget` characters with a count
+fn shorten_list(items: &[String], budget: usize) -> String {
+    // Leave room for the number of items left out
+    let budget = budget.saturating_sub(", +99999 more".len());
+    let mut list = String::new();
+    let mut shown = 0;
+    for item in items {
+        if list.len() + item.len() + 2 > budget {
+            break;
+        }
+        if shown > 0 {
+            list += ", ";
+        }
+        list += item;
+        shown += 1;
+    }
+    if shown < items.len() {
+        if shown > 0 {
+            list += ", ";
+        }
+        list += &format!("+{} more", items.len() - shown);
+    }
+    list
+}
+
+/// Parses a time given as `YYYY-MM-DD HH:MM` in the bot's local time zone
+fn parse_local_time(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+        .ok()
+        .and_then(|t| Local.from_local_datetime(&t).earliest())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Responds to the command with a placeholder message and returns it. Scheduler messages are
+/// always this bot-authored response edited in place, since the bot can't edit users' messages
+/// and slash commands don't leave a message of their own to reuse.
 async fn create_response(ctx: &Context, command: &ApplicationCommandInteraction) -> Message {
     command
         .create_interaction_response(&ctx.http, |response| {
@@ -62,7 +292,7 @@ async fn create_response(ctx: &Context, command: &ApplicationCommandInteraction)
         .expect("Cannot get message")
 }
 
-fn read_file(path: &Path) -> Option<(u64, Scheduler)> {
+fn read_file(path: &Path) -> Option<(u64, serde_json::Result<Scheduler>)> {
     let extension = path.extension().and_then(|e| e.to_str());
     if !matches!(extension, Some("json")) {
         return None;
@@ -74,11 +304,29 @@ fn read_file(path: &Path) -> Option<(u64, Scheduler)> {
         .unwrap()
         .parse()
         .expect("Cannot parse file name");
-    let file = File::open(path).expect("Cannot open file");
-    Some((
-        id,
-        serde_json::from_reader(file).expect("Cannot parse data"),
-    ))
+    let data = std::fs::read(path).expect("Cannot open file");
+    let scheduler = encryption::decrypt(data)
+        .map_err(serde_json::Error::io)
+        .and_then(|data| serde_json::from_slice(&data));
+    Some((id, scheduler))
+}
+
+/// Requires the privileged "Server Members" intent to be enabled for the bot
+async fn count_role_members(
+    ctx: &Context,
+    guild: GuildId,
+    role: RoleId,
+) -> serenity::Result<usize> {
+    let mut count = 0;
+    let mut after = None;
+    loop {
+        let members = guild.members(&ctx.http, Some(1000), after).await?;
+        count += members.iter().filter(|m| m.roles.contains(&role)).count();
+        match members.last() {
+            Some(m) if members.len() == 1000 => after = Some(m.user.id),
+            _ => return Ok(count),
+        }
+    }
 }
 
 fn file_path(id: &MessageId) -> PathBuf {
@@ -88,47 +336,233 @@ fn file_path(id: &MessageId) -> PathBuf {
     path
 }
 
-fn write_file(id: &MessageId, scheduler: &Scheduler) {
-    let file = File::create(file_path(id)).expect("Cannot create file");
-    serde_json::to_writer(file, &scheduler).expect("Cannot serialize data");
+/// Writes to a temporary file first so that readers never see a partially written file
+fn write_file(id: &MessageId, scheduler: &Scheduler) -> Result<(), SchedulerError> {
+    let path = file_path(id);
+    let temp_path = path.with_extension("json.tmp");
+    let data = encryption::encrypt(serde_json::to_vec(scheduler)?)?;
+    std::fs::write(&temp_path, data)?;
+    std::fs::rename(temp_path, path)?;
+    Ok(())
 }
 
 fn delete_file(id: &MessageId) {
-    std::fs::remove_file(file_path(id)).expect("Cannot delete file");
+    if let Err(e) = std::fs::remove_file(file_path(id)) {
+        error!("Cannot delete file: {}", e);
+    }
 }
 
-impl Handler {
-    fn new(refresh: bool) -> Self {
-        let data_dir = std::fs::metadata(DATA_DIR);
-        let is_dir = match data_dir {
-            Ok(f) => f.is_dir(),
-            Err(_) => false,
-        };
-        if !is_dir {
-            std::fs::create_dir(DATA_DIR).expect("Cannot create data dir");
-        }
-
-        let schedulers: Map<MessageId, Scheduler> = Map::new();
-        let reposts: Map<MessageId, MessageId> = Map::new();
-        let mut count = 0;
-        for f in std::fs::read_dir(DATA_DIR).expect("Cannot read data dir") {
-            let path = f.unwrap().path();
-            if let Some((id, s)) = read_file(&path) {
-                let id = id.into();
-                if let Some(repost) = s.get_repost() {
-                    reposts.insert(repost, id);
-                }
-                schedulers.insert(id, s);
-                count += 1;
+fn create_data_dir() {
+    let data_dir = std::fs::metadata(DATA_DIR);
+    let is_dir = match data_dir {
+        Ok(f) => f.is_dir(),
+        Err(_) => false,
+    };
+    if !is_dir {
+        std::fs::create_dir(DATA_DIR).expect("Cannot create data dir");
+    }
+}
+
+/// Writes every saved scheduler to a single timestamped file in `dir`, keyed by message id
+fn backup(dir: &Path) {
+    let registry = Registry::load_all();
+    let schedulers: Vec<Arc<Scheduler>> = registry.iter().collect();
+    let combined: HashMap<u64, &Scheduler> = schedulers
+        .iter()
+        .map(|s| (s.get_id().0, s.as_ref()))
+        .collect();
+    let mut path = dir.to_path_buf();
+    path.push(format!(
+        "schedulers-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let data = serde_json::to_vec(&combined).expect("Cannot serialize data");
+    let data = encryption::encrypt(data).expect("Cannot encrypt data");
+    std::fs::write(&path, data).expect("Cannot create backup file");
+    info!(
+        "backed up {} schedulers to {}",
+        combined.len(),
+        path.display()
+    );
+}
+
+/// Recreates the individual scheduler files from a file written by [`backup`]
+fn restore(path: &Path) {
+    let data = std::fs::read(path).expect("Cannot open backup file");
+    let data = encryption::decrypt(data).expect("Cannot decrypt backup");
+    let combined: HashMap<u64, Scheduler> =
+        serde_json::from_slice(&data).expect("Cannot parse backup");
+    for (id, scheduler) in combined.iter() {
+        write_file(&(*id).into(), scheduler).expect("Cannot write scheduler file");
+    }
+    info!("restored {} schedulers", combined.len());
+}
+
+/// Checks every saved scheduler for inconsistencies and logs the problems found, repairing them if
+/// `fix` is set. Files are only written when repairing.
+async fn validate(fix: bool) {
+    let http = env::var("DISCORD_TOKEN")
+        .ok()
+        .map(|token| Http::new(&token));
+    if http.is_none() {
+        warn!("DISCORD_TOKEN is not set; responders won't be checked against their role");
+    }
+    let (mut checked, mut failed) = (0, 0);
+    for f in std::fs::read_dir(DATA_DIR).expect("Cannot read data dir") {
+        let path = f.unwrap().path();
+        let (id, scheduler) = match read_file(&path) {
+            Some(result) => result,
+            None => continue,
+        };
+        checked += 1;
+        let mut scheduler = match scheduler {
+            Ok(scheduler) => scheduler,
+            Err(e) => {
+                failed += 1;
+                warn!("{}: cannot parse file ({})", id, e);
+                continue;
+            }
+        };
+        let problems = scheduler.validate(http.as_ref(), fix).await;
+        if problems.is_empty() {
+            info!("{}: ok", id);
+            continue;
+        }
+        failed += 1;
+        for problem in problems.iter() {
+            warn!("{}: {}", id, problem);
+        }
+        if fix {
+            match write_file(&id.into(), &scheduler) {
+                Ok(()) => info!("{}: repaired", id),
+                Err(e) => error!("{}: {}", id, e),
+            }
+        }
+    }
+    info!("{} schedulers checked, {} with problems", checked, failed);
+}
+
+fn send_daily_summaries(registry: &Registry, notifier: &Notifier, profiles: &Profiles) {
+    let mut summaries: HashMap<UserId, Vec<String>> = HashMap::new();
+    for scheduler in registry.iter().filter(|s| !s.is_closed()) {
+        let owner = scheduler.get_owner();
+        if profiles.get(owner).is_some_and(|p| p.daily_summary) {
+            summaries
+                .entry(owner)
+                .or_default()
+                .push(scheduler.get_summary(true));
+        }
+    }
+    for (owner, summaries) in summaries {
+        info!("sending daily summary to {}", owner);
+        let send = |content| {
+            notifier.send(Notification {
+                recipient: Recipient::User(owner),
+                reply_to: None,
+                content,
+                mention_role: None,
+            })
+        };
+        // Split across several messages if needed to stay within the length limit
+        let mut content = "Your open schedulers:".to_owned();
+        for summary in summaries {
+            if content.len() + summary.len() + 2 > MESSAGE_LIMIT {
+                send(std::mem::take(&mut content));
+            }
+            if !content.is_empty() {
+                content += "\n\n";
             }
+            content += &summary;
+        }
+        send(content);
+    }
+}
+
+/// Shows a newly created scheduler on its message, then saves and registers it
+async fn add_scheduler(ctx: &Context, registry: &Registry, scheduler: Scheduler) {
+    let message_id = scheduler.get_id();
+    info!(
+        "scheduler {} created in {}",
+        message_id,
+        scheduler.get_channel_name(scheduler.get_channel())
+    );
+    scheduler.update_messages(ctx).await;
+    if let Err(e) = write_file(&message_id, &scheduler) {
+        error!("{}", e);
+    }
+    registry.insert(scheduler).schedule_opening(ctx);
+}
+
+/// Posts a scheduler that was created with `/schedule post_later`, unless it was cancelled. The
+/// owner is sent a DM if it can't be posted.
+async fn post_pending(
+    ctx: &Context,
+    registry: &Registry,
+    notifier: &Notifier,
+    pending: &PendingPosts,
+    id: u64,
+) {
+    let post = match pending.take(id) {
+        Ok(Some(post)) => post,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Cannot remove pending post {}: {}", id, e);
+            return;
+        }
+    };
+    let PendingPost {
+        channel, builder, ..
+    } = post;
+    let (owner, title) = (builder.get_owner(), builder.get_title().to_owned());
+    let fail = |reason: String| {
+        notifier.send(Notification {
+            recipient: Recipient::User(owner),
+            reply_to: None,
+            content: format!("Cannot post **{}** in <#{}> ({})", title, channel, reason),
+            mention_role: None,
+        })
+    };
+    let message = match channel
+        .send_message(ctx, |m| m.content("Please wait..."))
+        .await
+    {
+        Ok(message) => message,
+        Err(e) => {
+            error!("Cannot post pending scheduler {}: {}", id, e);
+            fail(e.to_string());
+            return;
         }
-        info!("{} schedulers loaded", count);
+    };
+    let message_id = message.id;
+    match builder.build(message) {
+        Ok(scheduler) => {
+            scheduler.refresh_channel_names(ctx).await;
+            add_scheduler(ctx, registry, scheduler).await;
+        }
+        Err(e) => {
+            fail(e.to_string());
+            channel
+                .delete_message(ctx, message_id)
+                .await
+                .map_err(|e| error!("Cannot delete message: {}", e))
+                .ok();
+        }
+    }
+}
 
+impl Handler {
+    fn new(cli: &Cli) -> Self {
+        create_data_dir();
         Handler {
-            refresh,
-            schedulers,
-            reposts,
-            ..Default::default()
+            refresh: cli.refresh,
+            member_events: cli.member_events,
+            registry: Arc::new(Registry::load_all()),
+            notifier: Arc::new(Notifier::new(cli.notify_rate, cli.notify_burst)),
+            profiles: Arc::new(Profiles::load()),
+            pending: Arc::new(PendingPosts::load()),
+            #[cfg(feature = "dev-tools")]
+            dev_user: cli.dev_user.map(UserId),
+            startup_done: Default::default(),
         }
     }
 
@@ -142,14 +576,49 @@ impl Handler {
             .collect();
         match name {
             "create" => self.create_scheduler(ctx, &command, options).await,
+            "post_later" => self.create_pending_post(ctx, &command, options).await,
+            "pending" => self.manage_pending_posts(ctx, &command, options).await,
+            #[cfg(feature = "dev-tools")]
+            "dev_editor" => self.run_dev_editor(ctx, &command, options).await,
+            "preview" => self.preview_dates(ctx, &command, options).await,
             "repost" => self.repost_scheduler(ctx, &command, options).await,
+            "bump" => self.bump_scheduler(ctx, &command, options).await,
+            "results" => self.show_results(ctx, &command, options).await,
+            "block" => self.respond_for_role(ctx, &command, options).await,
+            "delete" => self.delete_scheduler(ctx, &command, options).await,
+            "recover" => self.recover_scheduler(ctx, &command, options).await,
+            "settings" => self.change_settings(ctx, &command, options).await,
+            "transfer" => self.transfer_schedulers(ctx, &command, options).await,
+            "stats" => self.show_stats(ctx, &command).await,
+            "nudge" => self.nudge_stale_responders(ctx, &command, options).await,
+            "export" => self.export_scheduler(ctx, &command, options).await,
+            "import" => self.import_scheduler(ctx, &command, options).await,
+            "merge" => self.merge_schedulers(ctx, &command, options).await,
+            "profile" => self.set_profile(ctx, &command, options).await,
             _ => panic!("Unexpected subcommand: {name}"),
         };
     }
 
-    fn get_scheduler(&self, id: MessageId) -> Option<ReadGuard<MessageId, Scheduler>> {
-        let id = self.reposts.get(&id).map(|g| *g.val()).unwrap_or(id);
-        return self.schedulers.get(&id);
+    fn get_scheduler(&self, id: MessageId) -> Option<Arc<Scheduler>> {
+        self.registry.get(id)
+    }
+
+    /// Looks up the scheduler given by the `id` option of a command
+    fn find_scheduler(&self, options: &HashMap<&str, &Value>) -> Option<Arc<Scheduler>> {
+        self.find_scheduler_option(options, "id")
+    }
+
+    fn find_scheduler_option(
+        &self,
+        options: &HashMap<&str, &Value>,
+        name: &str,
+    ) -> Option<Arc<Scheduler>> {
+        options
+            .get(name)?
+            .as_str()?
+            .parse::<u64>()
+            .ok()
+            .and_then(|id| self.get_scheduler(id.into()))
     }
 
     async fn create_scheduler(
@@ -162,7 +631,7 @@ impl Handler {
             .get("description")
             .expect("Cannot find description option");
         let title = title.as_str().expect("Caption has incorrect type");
-        if title.len() > 256 {
+        if title.len() > MAX_TITLE {
             send_error(&ctx, command, "Description is too long").await;
             return;
         }
@@ -170,166 +639,2101 @@ impl Handler {
             RoleId::from_str(v.as_str().expect("Group has incorrect type"))
                 .expect("Error parsing role")
         });
-        let limit = match options.get("limit") {
-            Some(limit) => limit.as_i64().expect("Limit has incorrect type"),
-            None => MAX_DATES as i64,
+        let DateOptions {
+            limit,
+            skip,
+            start_date,
+            days,
+            interval_weeks,
+        } = match parse_date_options(&options) {
+            Ok(o) => o,
+            Err(msg) => {
+                send_error(&ctx, command, &msg).await;
+                return;
+            }
         };
-        let days = options
-            .get("days")
-            .map(|s| {
-                s.as_str()
-                    .expect("Days has incorrect type")
-                    .split('+')
-                    .map(|d| Weekday::from_str(d).expect("Cannot parse day"))
-                    .collect::<HashSet<Weekday>>()
+        let opens_at = match options.get("opens_at") {
+            Some(v) => match parse_local_time(v.as_str().expect("Opens at has incorrect type")) {
+                Some(t) if t > Utc::now() => Some(t),
+                Some(_) => {
+                    send_error(&ctx, command, "Opening time can't be in the past").await;
+                    return;
+                }
+                None => {
+                    send_error(
+                        &ctx,
+                        command,
+                        "Opening time must be in YYYY-MM-DD HH:MM format",
+                    )
+                    .await;
+                    return;
+                }
+            },
+            None => None,
+        };
+        let quorum = options
+            .get("quorum")
+            .map(|v| v.as_u64().expect("Quorum has incorrect type") as usize);
+        let max_responders = options
+            .get("max_responders")
+            .map(|v| v.as_u64().expect("Max responders has incorrect type") as usize);
+        let weekday_averages = options
+            .get("weekday_averages")
+            .map(|v| v.as_bool().expect("Weekday averages has incorrect type"))
+            .unwrap_or(false);
+        let open_details = options
+            .get("open_details")
+            .map(|v| v.as_bool().expect("Open details has incorrect type"))
+            .unwrap_or(false);
+        let pin = options
+            .get("pin")
+            .map(|v| v.as_bool().expect("Pin has incorrect type"))
+            .unwrap_or(false);
+        let auto_bump = options
+            .get("auto_bump")
+            .map(|v| v.as_u64().expect("Auto bump has incorrect type") as usize);
+        let auto_bump_cooldown = options
+            .get("auto_bump_cooldown")
+            .map(|v| v.as_u64().expect("Auto bump cooldown has incorrect type"));
+        let reaction_nudge = options
+            .get("reaction_nudge")
+            .map(|v| v.as_bool().expect("Reaction nudge has incorrect type"))
+            .unwrap_or(false);
+        let show_blackout = options
+            .get("show_blackout")
+            .map(|v| v.as_bool().expect("Show blackout has incorrect type"))
+            .unwrap_or(false);
+        let auto_prune = options
+            .get("auto_prune")
+            .map(|v| v.as_bool().expect("Auto prune has incorrect type"))
+            .unwrap_or(false);
+        // Departures are only seen with member events, so the option would otherwise do nothing
+        if auto_prune && !self.member_events {
+            send_error(
+                &ctx,
+                command,
+                "Auto prune isn't available, since the bot isn't receiving member events",
+            )
+            .await;
+            return;
+        }
+        // Slash command options can't contain line breaks, so allow them to be escaped
+        let notes = options
+            .get("notes")
+            .map(|v| {
+                v.as_str()
+                    .expect("Notes has incorrect type")
+                    .replace("\\n", "\n")
+            })
+            .filter(|notes| !notes.trim().is_empty());
+        let hide_results = options
+            .get("hide_results")
+            .map(|v| v.as_bool().expect("Hide results has incorrect type"))
+            .unwrap_or(false);
+        let bars = options
+            .get("bars")
+            .map(|v| v.as_bool().expect("Bars has incorrect type"))
+            .unwrap_or(false);
+        let highlight = options
+            .get("highlight")
+            .map(|v| {
+                HighlightStyle::from_str(v.as_str().expect("Highlight has incorrect type"))
+                    .expect("Cannot parse highlight style")
             })
-            .unwrap_or_else(|| HashSet::from([Weekday::Sat, Weekday::Sun]));
-        let skip = options
-            .get("skip")
-            .map(|v| v.as_i64().expect("Skip has incorrect type"));
+            .unwrap_or_default();
+        let mark_soonest = options
+            .get("mark_soonest")
+            .map(|v| v.as_bool().expect("Mark soonest has incorrect type"))
+            .unwrap_or(false);
+        let seed = match options.get("seed") {
+            Some(v) => match v
+                .as_str()
+                .expect("Seed has incorrect type")
+                .parse::<u64>()
+                .ok()
+                .and_then(|id| self.get_scheduler(id.into()))
+            {
+                Some(s) => Some(s),
+                None => {
+                    send_error(&ctx, command, "Invalid seed message id").await;
+                    return;
+                }
+            },
+            None => None,
+        };
+        let builder = Scheduler::builder(command.user.id, title)
+            .group(group)
+            .limit(limit)
+            .skip(skip)
+            .start_date(start_date)
+            .weekdays(days)
+            .interval_weeks(interval_weeks)
+            .quorum(quorum)
+            .show_blackout(show_blackout)
+            .max_responders(max_responders)
+            .reaction_nudge(reaction_nudge)
+            .highlight(highlight)
+            .mark_soonest(mark_soonest)
+            .auto_prune(auto_prune)
+            .notes(notes)
+            .hide_results(hide_results)
+            .bars(bars)
+            .opens_at(opens_at)
+            .auto_bump(auto_bump, auto_bump_cooldown)
+            .weekday_averages(weekday_averages)
+            .open_details(open_details)
+            .locale(command.guild_locale.clone());
+        // Checked before the scheduler message is posted, so that invalid options don't leave it
+        // behind
+        if let Err(e) = builder.dates(Local::now().date_naive()) {
+            send_error(&ctx, command, &e.to_string()).await;
+            return;
+        }
         let message = create_response(&ctx, command).await;
-        let message_id = message.id;
-        let scheduler = Scheduler::new(command.user.id, group, message, limit, skip, title, days);
-        scheduler.update_messages(&ctx).await;
-        write_file(&message_id, &scheduler);
-        self.schedulers.insert(message_id, scheduler);
+        let scheduler = match builder.build(message) {
+            Ok(scheduler) => scheduler,
+            Err(e) => {
+                command
+                    .delete_original_interaction_response(&ctx)
+                    .await
+                    .map_err(|e| error!("Cannot delete response: {}", e))
+                    .ok();
+                command
+                    .create_followup_message(&ctx, |m| m.content(e.to_string()).ephemeral(true))
+                    .await
+                    .expect("Cannot send error response");
+                return;
+            }
+        };
+        if let Some(previous) = seed {
+            scheduler
+                .seed_responses(&ctx, command.guild_id, &previous)
+                .await;
+        }
+        scheduler.refresh_channel_names(&ctx).await;
+        if pin {
+            if let Err(e) = scheduler.set_pinned(&ctx, true).await {
+                error!("Cannot pin scheduler: {}", e);
+                command
+                    .create_followup_message(&ctx, |m| m.content(e.to_string()).ephemeral(true))
+                    .await
+                    .map_err(|e| error!("Cannot send error response: {}", e))
+                    .ok();
+            }
+        }
+        add_scheduler(&ctx, &self.registry, scheduler).await;
     }
 
-    async fn repost_scheduler(
+    /// Saves a scheduler to be posted in the command's channel at a later time
+    async fn create_pending_post(
         &self,
         ctx: Context,
         command: &ApplicationCommandInteraction,
         options: HashMap<&str, &Value>,
     ) {
-        let scheduler_guard = match options
-            .get("id")
-            .unwrap()
+        let title = options
+            .get("description")
+            .expect("Cannot find description option");
+        let title = title.as_str().expect("Caption has incorrect type");
+        let group = options.get("group").map(|v| {
+            RoleId::from_str(v.as_str().expect("Group has incorrect type"))
+                .expect("Error parsing role")
+        });
+        let DateOptions {
+            limit,
+            skip,
+            start_date,
+            days,
+            interval_weeks,
+        } = match parse_date_options(&options) {
+            Ok(o) => o,
+            Err(msg) => {
+                send_error(&ctx, command, &msg).await;
+                return;
+            }
+        };
+        let post_at = options
+            .get("post_at")
+            .expect("Cannot find post at option")
             .as_str()
-            .unwrap()
-            .parse::<u64>()
-            .ok()
-            .and_then(|id| self.get_scheduler(id.into()))
-        {
-            Some(s) => s,
+            .expect("Post at has incorrect type");
+        let post_at = match parse_local_time(post_at) {
+            Some(t) if t > Utc::now() => t,
+            Some(_) => {
+                send_error(&ctx, command, "Posting time can't be in the past").await;
+                return;
+            }
             None => {
-                send_error(&ctx, command, "Invalid message id").await;
+                send_error(
+                    &ctx,
+                    command,
+                    "Posting time must be in YYYY-MM-DD HH:MM format",
+                )
+                .await;
                 return;
             }
         };
-        let scheduler = scheduler_guard.val();
-
-        let message = create_response(&ctx, command).await;
-        let repost_id = message.id;
-        scheduler.repost(&ctx, Some(message)).await;
-        self.reposts.insert(repost_id, scheduler.get_id());
+        let builder = Scheduler::builder(command.user.id, title)
+            .group(group)
+            .limit(limit)
+            .skip(skip)
+            .start_date(start_date)
+            .weekdays(days)
+            .interval_weeks(interval_weeks)
+            .locale(command.guild_locale.clone());
+        // Checked now so that the owner finds out about invalid options straight away, though
+        // the dates are generated again when it is posted
+        if let Err(e) = builder.dates(post_at.with_timezone(&Local).date_naive()) {
+            send_error(&ctx, command, &e.to_string()).await;
+            return;
+        }
+        let id = command.id.0;
+        let post = PendingPost {
+            channel: command.channel_id,
+            post_at,
+            builder,
+        };
+        let text = match self.pending.insert(id, post) {
+            Ok(()) => {
+                info!("scheduler {} will be posted at {}", id, post_at);
+                self.schedule_post(&ctx, id, post_at);
+                format!(
+                    "**{}** will be posted in this channel <t:{}:f>. Use `/schedule pending` to \
+                     cancel it.",
+                    title,
+                    post_at.timestamp()
+                )
+            }
+            Err(e) => e.to_string(),
+        };
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
     }
 
-    async fn handle_get_response(
+    /// Runs the response editor on a scheduler with a script of actions and random dates,
+    /// submitting a response for the developer, and reports what the editor showed
+    #[cfg(feature = "dev-tools")]
+    async fn run_dev_editor(
         &self,
         ctx: Context,
-        component: &MessageComponentInteraction,
-        resp_type: ResponseType,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
     ) {
-        let message_id = match resp_type {
-            ResponseType::Normal => component.message.id,
-            ResponseType::Blackout => component
-                .message
-                .message_reference
-                .as_ref()
-                .expect("Cannot find message for DM")
-                .message_id
-                .unwrap(),
+        use ring::rand::SecureRandom;
+
+        if self.dev_user != Some(command.user.id) {
+            send_error(&ctx, command, "Only the developer may use this command").await;
+            return;
+        }
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
         };
-        let scheduler = self
-            .get_scheduler(message_id)
-            .expect("Cannot find scheduler");
+        // Submitting updates the scheduler message, which can wait for the edit interval
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|m| m.ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        let mut seed = [0; MAX_DATES];
+        ring::rand::SystemRandom::new()
+            .fill(&mut seed)
+            .expect("Cannot generate random dates");
+        let (actions, expected) = scheduler.get_dev_script(&seed);
+        let mut session = ScriptedSession::new(command.user.id, command.guild_id, actions);
         scheduler
-            .val()
-            .get_response(&ctx, component, resp_type)
+            .get_response(&ctx, &mut session, ResponseType::Normal, &self.profiles)
+            .await;
+        let saved = scheduler.get_response_dates(command.user.id);
+        let format_dates = |dates: &[NaiveDate]| {
+            let dates: Vec<String> = dates
+                .iter()
+                .map(|d| d.format("%a %b %d").to_string())
+                .collect();
+            dates.join(", ")
+        };
+        let mut text = format!(
+            "Expected: {}\nSaved: {}\n{}\n",
+            format_dates(&expected),
+            saved
+                .as_deref()
+                .map_or("no response".to_owned(), format_dates),
+            if saved.as_ref() == Some(&expected) {
+                "✅ Response matches"
+            } else {
+                "❌ Response doesn't match"
+            }
+        );
+        for (i, line) in session.into_transcript().into_iter().enumerate() {
+            let line = format!("{}. {}\n", i + 1, line);
+            if text.len() + line.len() > MESSAGE_LIMIT {
+                break;
+            }
+            text += &line;
+        }
+        command
+            .create_followup_message(&ctx, |m| m.content(text).ephemeral(true))
             .await
+            .expect("Cannot send message");
     }
 
-    async fn handle_show_details(&self, ctx: Context, component: &MessageComponentInteraction) {
-        let message_id = component.message.id;
-        let scheduler = self
-            .get_scheduler(message_id)
-            .expect("Cannot find scheduler");
-        scheduler.val().show_details(&ctx, component).await;
+    /// Lists the user's pending posts, or cancels one
+    async fn manage_pending_posts(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let text = match options.get("cancel") {
+            Some(v) => {
+                let id = v.as_str().expect("Cancel has incorrect type").trim();
+                match id
+                    .parse()
+                    .map(|id| self.pending.cancel(id, command.user.id))
+                {
+                    Ok(Ok(true)) => "Post cancelled".to_owned(),
+                    Ok(Err(e)) => e.to_string(),
+                    _ => "You have no pending post with that id".to_owned(),
+                }
+            }
+            None => {
+                let mut text = "Your pending posts:".to_owned();
+                let posts = self.pending.list(command.user.id);
+                if posts.is_empty() {
+                    text = "You have no pending posts".to_owned();
+                }
+                for post in posts {
+                    if text.len() + post.len() + 1 > MESSAGE_LIMIT {
+                        break;
+                    }
+                    text += "\n";
+                    text += &post;
+                }
+                text
+            }
+        };
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
     }
 
-    async fn do_initialization(&self, ctx: &Context) {
-        info!("registering");
-        Command::create_global_application_command(&ctx, |command| {
-            command
-                .name("schedule")
-                .description("scheduler")
-                .create_option(|o| {
-                    o.name("create")
-                        .kind(CommandOptionType::SubCommand)
-                        .description("Create a scheduler")
-                        .create_sub_option(|o| {
-                            o.name("description")
-                                .description("event description")
-                                .kind(CommandOptionType::String)
-                                .required(true)
-                        })
-                        .create_sub_option(|o| {
-                            o.name("group")
-                                .description("player group")
-                                .kind(CommandOptionType::Role)
-                        })
-                        .create_sub_option(|o| {
-                            o.name("limit")
-                                .description("number of dates to include")
-                                .kind(CommandOptionType::Integer)
-                                .min_int_value(1)
-                                .max_int_value(MAX_DATES)
-                        })
-                        .create_sub_option(|o| {
-                            o.name("skip")
-                                .description("weeks before start")
-                                .kind(CommandOptionType::Integer)
-                                .min_int_value(0)
-                        })
-                        .create_sub_option(|o| {
-                            o.name("days")
-                                .description("weekdays to include")
-                                .kind(CommandOptionType::String)
-                                .add_string_choice("Saturday + Sunday", "Sat+Sun")
-                                .add_string_choice("Sunday", "Sun")
-                                .add_string_choice("Monday", "Mon")
-                                .add_string_choice("Tuesday", "Tue")
-                                .add_string_choice("Wednesday", "Wed")
-                                .add_string_choice("Thursday", "Thu")
-                                .add_string_choice("Friday", "Fri")
-                                .add_string_choice("Saturday", "Sat")
-                        })
-                })
-                .create_option(|o| {
-                    o.name("repost")
-                        .kind(CommandOptionType::SubCommand)
-                        .description("Repost a scheduler message")
-                        .create_sub_option(|o| {
-                            o.name("id")
-                                .description("message id")
-                                .kind(CommandOptionType::String)
-                                .required(true)
-                        })
-                })
-        })
-        .await
-        .expect("Cannot create command");
+    fn schedule_post(&self, ctx: &Context, id: u64, post_at: DateTime<Utc>) {
+        let registry = self.registry.clone();
+        let notifier = self.notifier.clone();
+        let pending = self.pending.clone();
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            if let Ok(delay) = (post_at - Utc::now()).to_std() {
+                tokio::time::sleep(delay).await;
+            }
+            post_pending(&ctx, &registry, &notifier, &pending, id).await;
+        });
+    }
 
-        if self.refresh {
-            for entry in self.schedulers.iter() {
-                let scheduler = entry.val();
-                scheduler.update_messages(ctx).await;
+    async fn repost_scheduler(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
             }
-        }
+        };
+        let message = create_response(&ctx, command).await;
+        self.set_repost(&ctx, &scheduler, message).await;
     }
-}
 
-#[async_trait]
+    /// Replaces the scheduler's repost with `message`
+    async fn set_repost(&self, ctx: &Context, scheduler: &Scheduler, message: Message) {
+        // Forget the old repost first, so that deleting it isn't handled as the repost
+        // disappearing
+        if let Some(old_id) = scheduler.get_repost() {
+            self.registry.remove_repost(old_id);
+        }
+        let repost_id = message.id;
+        scheduler.repost(ctx, Some(message)).await;
+        self.registry.add_repost(repost_id, scheduler);
+    }
+
+    /// Reposts a scheduler at the bottom of the channel its most recent copy is in
+    async fn bump(&self, ctx: &Context, scheduler: &Scheduler) -> serenity::Result<MessageId> {
+        let message = scheduler
+            .get_latest_channel()
+            .send_message(ctx, |m| m.content("Please wait..."))
+            .await?;
+        let message_id = message.id;
+        self.set_repost(ctx, scheduler, message).await;
+        Ok(message_id)
+    }
+
+    async fn bump_scheduler(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        if command.user.id != scheduler.get_owner() {
+            send_error(&ctx, command, "Only the scheduler owner may bump it").await;
+            return;
+        }
+        if let Err(remaining) = scheduler.start_bump() {
+            let msg = format!(
+                "This scheduler was bumped recently; try again in {} minutes",
+                remaining.as_secs() / 60 + 1
+            );
+            send_error(&ctx, command, &msg).await;
+            return;
+        }
+
+        let channel_id = scheduler.get_latest_channel();
+        let message_id = match self.bump(&ctx, &scheduler).await {
+            Ok(message_id) => message_id,
+            Err(e) => {
+                error!("Cannot bump scheduler: {}", e);
+                send_error(&ctx, command, "Cannot post in the scheduler's channel").await;
+                return;
+            }
+        };
+        let link = format!(
+            "https://discord.com/channels/{}/{}/{}",
+            command.guild_id.map_or("@me".to_owned(), |g| g.to_string()),
+            channel_id,
+            message_id
+        );
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| {
+                        m.content(format!("Scheduler bumped: {}", link))
+                            .ephemeral(true)
+                    })
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    async fn show_results(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        let guild = match scheduler.get_channel().to_channel(&ctx).await {
+            Ok(Channel::Guild(channel)) => Some(channel.guild_id),
+            _ => None,
+        };
+        if guild.is_none() || guild != command.guild_id {
+            send_error(&ctx, command, "That scheduler isn't in this server").await;
+            return;
+        }
+        let table = options
+            .get("format")
+            .is_some_and(|v| v.as_str().expect("Format has incorrect type") == "table");
+        if table {
+            self.show_results_tables(ctx, command, &scheduler).await;
+            return;
+        }
+        let summary = scheduler.get_summary(scheduler.can_see_results(command.user.id));
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(summary).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    async fn set_profile(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let user = command.user.id;
+        let clear = options
+            .get("clear")
+            .map(|v| v.as_bool().expect("Clear has incorrect type"))
+            .unwrap_or(false);
+        let mut profile = match clear {
+            true => Profile::default(),
+            false => self.profiles.get(user).unwrap_or_default(),
+        };
+        if let Some(days) = options.get("days") {
+            profile.weekdays = parse_days(days);
+        }
+        if let Some(text_responses) = options.get("text_responses") {
+            profile.text_responses = text_responses
+                .as_bool()
+                .expect("Text responses has incorrect type");
+        }
+        if let Some(daily_summary) = options.get("daily_summary") {
+            profile.daily_summary = daily_summary
+                .as_bool()
+                .expect("Daily summary has incorrect type");
+        }
+        if let Err(e) = self.profiles.set(user, profile.clone()) {
+            error!("{}", e);
+            send_error(&ctx, command, &e.to_string()).await;
+            return;
+        }
+        let text = if profile.is_empty() {
+            "You don't have a profile".to_owned()
+        } else {
+            let mut weekdays: Vec<Weekday> = profile.weekdays.into_iter().collect();
+            weekdays.sort_by_key(|d| d.num_days_from_monday());
+            let mut parts: Vec<String> = weekdays.iter().map(|d| format!("every {}", d)).collect();
+            if !profile.dates.is_empty() {
+                parts.push(format!("{} saved dates", profile.dates.len()));
+            }
+            if profile.text_responses {
+                parts.push("responding by DM".to_owned());
+            }
+            if profile.daily_summary {
+                parts.push("daily summary of your open schedulers".to_owned());
+            }
+            format!(
+                "Your profile: {}. Apply it from \"Copy a response\" when responding.",
+                parts.join(", ")
+            )
+        };
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    /// Lists the dates `/schedule create` would include with the same options, without creating
+    /// a scheduler
+    async fn preview_dates(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let DateOptions {
+            limit,
+            skip,
+            start_date,
+            days,
+            interval_weeks,
+        } = match parse_date_options(&options) {
+            Ok(o) => o,
+            Err(msg) => {
+                send_error(&ctx, command, &msg).await;
+                return;
+            }
+        };
+        let dates = match Scheduler::generate_dates(
+            limit,
+            skip,
+            start_date,
+            &days,
+            interval_weeks,
+            Local::now().date_naive(),
+        ) {
+            Ok(dates) => dates,
+            Err(e) => {
+                send_error(&ctx, command, &e.to_string()).await;
+                return;
+            }
+        };
+        let mut text = format!("These options would include {} dates:", dates.len());
+        if dates.len() as i64 != limit {
+            text += &format!(
+                "\n(the limit of {} is rounded down to a multiple of the {} weekdays included)",
+                limit,
+                days.len()
+            );
+        }
+        for date in dates.iter() {
+            text += &format!("\n{}", date.format("%a %Y-%m-%d"));
+        }
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    async fn change_settings(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        if command.user.id != scheduler.get_owner() {
+            send_error(&ctx, command, &SchedulerError::NotOwner.to_string()).await;
+            return;
+        }
+        // The scheduler id is the only option that isn't a setting
+        if options.len() < 2 {
+            send_error(&ctx, command, "No settings were given").await;
+            return;
+        }
+
+        let mut result = Ok(());
+        if let Some(v) = options.get("blackout_reason") {
+            let required = v.as_bool().expect("Blackout reason has incorrect type");
+            result = result.and(scheduler.set_require_blackout_reason(command.user.id, required));
+        }
+        if let Some(v) = options.get("coverage_dates") {
+            let dates = v.as_u64().expect("Coverage dates has incorrect type") as usize;
+            result = result.and(scheduler.set_coverage_dates(command.user.id, dates));
+        }
+        if let Some(v) = options.get("target") {
+            let target = v.as_u64().expect("Target has incorrect type") as usize;
+            let target = Some(target).filter(|t| *t > 0);
+            result = result.and(scheduler.set_target(command.user.id, target));
+        }
+        if let Some(v) = options.get("leading") {
+            let show = v.as_bool().expect("Leading has incorrect type");
+            result = result.and(scheduler.set_show_leading(command.user.id, show));
+        }
+        if let Some(v) = options.get("month_headers") {
+            let show = v.as_bool().expect("Month headers has incorrect type");
+            result = result.and(scheduler.set_month_headers(command.user.id, show));
+        }
+        if let Some(v) = options.get("shortlist") {
+            let count = v.as_u64().expect("Shortlist has incorrect type") as usize;
+            result = result.and(scheduler.set_shortlist(command.user.id, count));
+        }
+        if let Some(v) = options.get("time_ranges") {
+            let enabled = v.as_bool().expect("Time ranges has incorrect type");
+            result = result.and(scheduler.set_time_ranges(command.user.id, enabled));
+        }
+        if let Some(v) = options.get("blackout_past") {
+            let enabled = v.as_bool().expect("Blackout past has incorrect type");
+            result = result.and(scheduler.set_blackout_past(command.user.id, enabled));
+        }
+        if let Some(v) = options.get("role_loss") {
+            let policy =
+                RoleLossPolicy::from_str(v.as_str().expect("Role loss has incorrect type"))
+                    .expect("Cannot parse role loss policy");
+            // Role changes are only seen with member events, so the policy would otherwise do
+            // nothing
+            result = result.and(if policy != RoleLossPolicy::Keep && !self.member_events {
+                Err(SchedulerError::InvalidOptions(
+                    "Role loss handling isn't available, since the bot isn't receiving member \
+                     events"
+                        .to_owned(),
+                ))
+            } else {
+                scheduler.set_role_loss(command.user.id, policy)
+            });
+        }
+        if let Some(v) = options.get("tie_break") {
+            let tie_break = TieBreak::from_str(v.as_str().expect("Tie break has incorrect type"))
+                .expect("Cannot parse tie break");
+            result = result.and(scheduler.set_tie_break(command.user.id, tie_break));
+        }
+        if let Some(v) = options.get("preselect_all") {
+            let enabled = v.as_bool().expect("Preselect all has incorrect type");
+            result = result.and(scheduler.set_preselect_all(command.user.id, enabled));
+        }
+        if let Some(v) = options.get("visibility") {
+            let visibility =
+                ResultsVisibility::from_str(v.as_str().expect("Visibility has incorrect type"))
+                    .expect("Cannot parse visibility");
+            result = result.and(scheduler.set_visibility(command.user.id, visibility));
+        }
+        for (name, is_organizer) in [("add_organizer", true), ("remove_organizer", false)] {
+            if let Some(v) = options.get(name) {
+                let organizer = UserId::from_str(v.as_str().expect("Organizer has incorrect type"))
+                    .expect("Error parsing user");
+                result =
+                    result.and(scheduler.set_organizer(command.user.id, organizer, is_organizer));
+            }
+        }
+        let text = match result {
+            Ok(()) => "Settings updated".to_owned(),
+            Err(e) => e.to_string(),
+        };
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        scheduler.update_messages(&ctx).await;
+    }
+
+    /// Shows a scheduler's results as markdown tables that can be pasted into other apps
+    async fn show_results_tables(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        scheduler: &Scheduler,
+    ) {
+        if !scheduler.can_see_results(command.user.id) {
+            send_error(
+                &ctx,
+                command,
+                scheduler.get_hidden_results_note(command.user.id),
+            )
+            .await;
+            return;
+        }
+        // Looking up the responders' names can take a while
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|m| m.ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        let detailed = scheduler.can_see_details(command.user.id);
+        let tables = scheduler
+            .get_results_tables(&ctx, command.guild_id, detailed)
+            .await;
+        if tables.is_empty() {
+            command
+                .create_followup_message(&ctx, |m| m.content("No dates to show").ephemeral(true))
+                .await
+                .expect("Cannot send message");
+        }
+        for table in tables {
+            command
+                .create_followup_message(&ctx, |m| m.content(table).ephemeral(true))
+                .await
+                .expect("Cannot send message");
+        }
+    }
+
+    /// Sends the owner a scheduler's saved state as a file, which can be imported elsewhere
+    async fn export_scheduler(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        if command.user.id != scheduler.get_owner() {
+            send_error(&ctx, command, &SchedulerError::NotOwner.to_string()).await;
+            return;
+        }
+        let export = serde_json::json!({ "version": EXPORT_VERSION, "scheduler": &*scheduler });
+        let data = serde_json::to_vec_pretty(&export).expect("Cannot serialize scheduler");
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| {
+                        m.ephemeral(true)
+                            .content(format!(
+                                "**{}** in {}",
+                                scheduler.get_title(),
+                                scheduler.get_channel_name(scheduler.get_channel())
+                            ))
+                            .add_file(AttachmentType::Bytes {
+                                data: data.into(),
+                                filename: format!("{}.json", scheduler.get_id()),
+                            })
+                    })
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    /// Recreates an exported scheduler with a new message in the current channel, owned by the
+    /// user importing it
+    async fn import_scheduler(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let attachment = options
+            .get("file")
+            .and_then(|v| v.as_str())
+            .and_then(|id| id.parse::<u64>().ok())
+            .map(AttachmentId)
+            .and_then(|id| command.data.resolved.attachments.get(&id));
+        let attachment = match attachment {
+            Some(a) => a,
+            None => {
+                send_error(&ctx, command, "Cannot find the attached file").await;
+                return;
+            }
+        };
+        if attachment.size > MAX_IMPORT_SIZE {
+            let msg = format!(
+                "The file is too large to be an exported scheduler (over {} MB)",
+                MAX_IMPORT_SIZE / (1024 * 1024)
+            );
+            send_error(&ctx, command, &msg).await;
+            return;
+        }
+        let data = match attachment.download().await {
+            Ok(data) => data,
+            Err(e) => {
+                send_error(&ctx, command, &SchedulerError::from(e).to_string()).await;
+                return;
+            }
+        };
+        let mut export: Value = match serde_json::from_slice(&data) {
+            Ok(v) => v,
+            Err(e) => {
+                let msg = format!("Cannot read the scheduler from the file ({})", e);
+                send_error(&ctx, command, &msg).await;
+                return;
+            }
+        };
+        match export.get("version").and_then(Value::as_u64) {
+            Some(version) if version <= EXPORT_VERSION => (),
+            Some(_) => {
+                let msg = "The file was exported by a newer version of the bot";
+                send_error(&ctx, command, msg).await;
+                return;
+            }
+            None => {
+                send_error(&ctx, command, "The file isn't an exported scheduler").await;
+                return;
+            }
+        }
+        // Fields added since the file was exported take their defaults
+        let mut scheduler: Scheduler = match serde_json::from_value(export["scheduler"].take()) {
+            Ok(s) => s,
+            Err(e) => {
+                let msg = format!("Cannot read the scheduler from the file ({})", e);
+                send_error(&ctx, command, &msg).await;
+                return;
+            }
+        };
+        // Nothing is posted for a scheduler that can't be repaired
+        if let Err(e) = scheduler.check_dates() {
+            send_error(&ctx, command, &e.to_string()).await;
+            return;
+        }
+        let problems = scheduler.validate(None, true).await;
+
+        let message = create_response(&ctx, command).await;
+        let message_id = message.id;
+        let channel_id = message.channel_id;
+        scheduler.rebind(&ctx, command.user.id, command.guild_id, message);
+        scheduler.refresh_channel_names(&ctx).await;
+        info!(
+            "scheduler {} imported in {}",
+            message_id,
+            scheduler.get_channel_name(channel_id)
+        );
+        scheduler.update_messages(&ctx).await;
+        if let Err(e) = write_file(&message_id, &scheduler) {
+            error!("{}", e);
+        }
+        self.registry.insert(scheduler).schedule_opening(&ctx);
+        if !problems.is_empty() {
+            let text = format!(
+                "Fixed {} problems in the imported scheduler:\n{}",
+                problems.len(),
+                problems.join("\n")
+            );
+            command
+                .create_followup_message(&ctx, |m| m.content(text).ephemeral(true))
+                .await
+                .map_err(|e| error!("Cannot send response: {}", e))
+                .ok();
+        }
+    }
+
+    /// Tells the user how many of the server's schedulers they have responded to
+    async fn show_stats(&self, ctx: Context, command: &ApplicationCommandInteraction) {
+        let guild_id = match command.guild_id {
+            Some(guild_id) => guild_id,
+            None => {
+                send_error(&ctx, command, "Stats are only available in a server").await;
+                return;
+            }
+        };
+        let user = command.user.id;
+        let (mut total, mut responded) = (0, 0);
+        for scheduler in self.registry.iter() {
+            if in_guild(&ctx, scheduler.get_channel(), guild_id).await {
+                total += 1;
+                if scheduler.has_responded(user) {
+                    responded += 1;
+                }
+            }
+        }
+        let text = if total == 0 {
+            "There are no schedulers in this server yet".to_owned()
+        } else {
+            format!(
+                "You've responded to {} of the {} schedulers in this server ({}%)",
+                responded,
+                total,
+                responded * 100 / total
+            )
+        };
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    async fn nudge_stale_responders(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        let guild_id = match command.guild_id {
+            Some(guild_id) => guild_id,
+            None => {
+                send_error(&ctx, command, "Responders can only be nudged from a server").await;
+                return;
+            }
+        };
+        let days = options
+            .get("days")
+            .map(|v| v.as_i64().expect("Days has incorrect type"))
+            .unwrap_or(STALE_RESPONSE_DAYS);
+        let clear_after = options.get("clear_after").map(|v| {
+            let hours = v.as_u64().expect("Clear after has incorrect type");
+            std::time::Duration::from_secs(hours * 60 * 60)
+        });
+        let text = match scheduler.nudge_stale_responders(
+            &ctx,
+            &self.notifier,
+            command.user.id,
+            guild_id,
+            chrono::Duration::days(days),
+            clear_after,
+        ) {
+            Ok(0) => format!("No responses are older than {} days", days),
+            Ok(count) => format!("Asked {} responders to update their responses", count),
+            Err(e) => e.to_string(),
+        };
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    /// Hands every scheduler in the server owned by one user over to another, e.g. when an
+    /// organizer leaves
+    async fn transfer_schedulers(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let is_admin = command
+            .member
+            .as_ref()
+            .and_then(|m| m.permissions)
+            .is_some_and(|p| p.administrator());
+        let guild_id = match command.guild_id {
+            Some(guild_id) if is_admin => guild_id,
+            _ => {
+                send_error(
+                    &ctx,
+                    command,
+                    "Only server administrators may transfer schedulers",
+                )
+                .await;
+                return;
+            }
+        };
+        let user_option = |name| {
+            UserId::from_str(options.get(name).unwrap().as_str().unwrap())
+                .expect("Error parsing user")
+        };
+        let (from, to) = (user_option("from"), user_option("to"));
+
+        let mut transferred = vec![];
+        let mut failed = 0;
+        for scheduler in self.registry.iter() {
+            if scheduler.get_owner() != from
+                || !in_guild(&ctx, scheduler.get_channel(), guild_id).await
+            {
+                continue;
+            }
+            if let Err(e) = scheduler.set_owner(to) {
+                error!("{}", e);
+                failed += 1;
+            }
+            transferred.push(scheduler);
+        }
+        info!(
+            "transferred {} schedulers from {} to {}",
+            transferred.len(),
+            from,
+            to
+        );
+        let mut text = format!(
+            "Transferred {} schedulers from <@{}> to <@{}>",
+            transferred.len(),
+            from,
+            to
+        );
+        if failed > 0 {
+            text += &format!("; {} couldn't be saved", failed);
+        }
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        // The owner's name is shown on the scheduler messages
+        for scheduler in transferred {
+            scheduler.update_messages(&ctx).await;
+        }
+    }
+
+    async fn recover_scheduler(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        if command.user.id != scheduler.get_owner() {
+            send_error(&ctx, command, &SchedulerError::NotOwner.to_string()).await;
+            return;
+        }
+        let force = options
+            .get("force")
+            .map(|v| v.as_bool().expect("Force has incorrect type"))
+            .unwrap_or(false);
+        let exists = match scheduler.message_exists(&ctx).await {
+            Ok(true) if !force => {
+                send_error(
+                    &ctx,
+                    command,
+                    "The scheduler message still exists. Use `force` to replace it anyway.",
+                )
+                .await;
+                return;
+            }
+            Ok(exists) => exists,
+            Err(e) => {
+                send_error(&ctx, command, &SchedulerError::from(e).to_string()).await;
+                return;
+            }
+        };
+
+        let old_id = scheduler.get_id();
+        let message = create_response(&ctx, command).await;
+        let old_message = scheduler.recover(&ctx, message).await;
+        self.registry.rekey(old_id, scheduler);
+        // Only deleted once the scheduler is no longer registered under the old id, so that
+        // this isn't handled as the scheduler being deleted
+        if exists {
+            if let Err(e) = old_message.delete(&ctx).await {
+                error!("can't delete old scheduler message: {e}");
+            }
+        }
+    }
+
+    async fn delete_scheduler(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        let is_admin = command
+            .member
+            .as_ref()
+            .and_then(|m| m.permissions)
+            .is_some_and(|p| p.administrator());
+        if command.user.id != scheduler.get_owner() && !is_admin {
+            send_error(&ctx, command, "Only the scheduler owner may delete it").await;
+            return;
+        }
+
+        let mut session = CommandSession::new(&ctx, command);
+        let mut components = CreateComponents::default();
+        components.create_action_row(|ar| {
+            ar.create_button(|b| {
+                b.style(ButtonStyle::Danger)
+                    .label("Delete")
+                    .custom_id("confirm")
+            })
+            .create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label("Cancel")
+                    .custom_id("cancel")
+            })
+        });
+        let prompt = format!(
+            "Delete **{}** and all of its responses? This cannot be undone.",
+            scheduler.get_title()
+        );
+        session
+            .respond(Some(prompt), components)
+            .await
+            .expect("Cannot send response");
+        let confirmed = matches!(
+            session.next_action(CONFIRM_TIMEOUT).await,
+            Some(action) if action.custom_id == "confirm"
+        );
+        if !confirmed {
+            session
+                .edit(Some("Cancelled".to_owned()), CreateComponents::default())
+                .await
+                .map_err(|e| error!("Cannot update message: {}", e))
+                .ok();
+            return;
+        }
+
+        if let Some(scheduler) = self.registry.remove(scheduler.get_id()) {
+            scheduler.destroy(&ctx).await;
+        }
+        session
+            .edit(
+                Some("Scheduler deleted".to_owned()),
+                CreateComponents::default(),
+            )
+            .await
+            .map_err(|e| error!("Cannot update message: {}", e))
+            .ok();
+    }
+
+    async fn merge_schedulers(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let (source, target) = match (
+            self.find_scheduler_option(&options, "source"),
+            self.find_scheduler_option(&options, "target"),
+        ) {
+            (Some(source), Some(target)) => (source, target),
+            _ => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        if source.get_id() == target.get_id() {
+            send_error(&ctx, command, "Cannot merge a scheduler into itself").await;
+            return;
+        }
+        if command.user.id != source.get_owner() || command.user.id != target.get_owner() {
+            send_error(
+                &ctx,
+                command,
+                "Only the owner of both schedulers may merge them",
+            )
+            .await;
+            return;
+        }
+
+        let mut session = CommandSession::new(&ctx, command);
+        let mut components = CreateComponents::default();
+        components.create_action_row(|ar| {
+            ar.create_button(|b| {
+                b.style(ButtonStyle::Danger)
+                    .label("Merge")
+                    .custom_id("confirm")
+            })
+            .create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label("Cancel")
+                    .custom_id("cancel")
+            })
+        });
+        let prompt = format!(
+            "Merge the responses to **{}** into **{}**? **{}** will be deleted afterwards.",
+            source.get_title(),
+            target.get_title(),
+            source.get_title()
+        );
+        session
+            .respond(Some(prompt), components)
+            .await
+            .expect("Cannot send response");
+        let confirmed = matches!(
+            session.next_action(CONFIRM_TIMEOUT).await,
+            Some(action) if action.custom_id == "confirm"
+        );
+        if !confirmed {
+            session
+                .edit(Some("Cancelled".to_owned()), CreateComponents::default())
+                .await
+                .map_err(|e| error!("Cannot update message: {}", e))
+                .ok();
+            return;
+        }
+
+        let text = match target.merge_from(&ctx, &source).await {
+            Ok(report) => {
+                if let Some(source) = self.registry.remove(source.get_id()) {
+                    source.destroy(&ctx).await;
+                }
+                let mut text = format!("Merged {} responses", report.merged);
+                let mut lists = vec![];
+                if !report.left_out.is_empty() {
+                    let left_out = report
+                        .left_out
+                        .iter()
+                        .map(|user_id| format!("<@{}>", user_id))
+                        .collect::<Vec<_>>();
+                    lists.push(("Left out because the scheduler is full", left_out));
+                }
+                if !report.conflicts.is_empty() {
+                    let conflicts = report
+                        .conflicts
+                        .iter()
+                        .map(|(user_id, newer)| {
+                            format!(
+                                "<@{}> (kept {} response)",
+                                user_id,
+                                if *newer { "merged" } else { "existing" }
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    lists.push(("Responded to both", conflicts));
+                }
+                for (heading, items) in lists {
+                    let budget = MESSAGE_LIMIT.saturating_sub(text.len() + heading.len() + 3);
+                    text += &format!("\n{}: {}", heading, shorten_list(&items, budget));
+                }
+                text
+            }
+            Err(e) => e.to_string(),
+        };
+        session
+            .edit(Some(text), CreateComponents::default())
+            .await
+            .map_err(|e| error!("Cannot update message: {}", e))
+            .ok();
+    }
+
+    async fn respond_for_role(
+        &self,
+        ctx: Context,
+        command: &ApplicationCommandInteraction,
+        options: HashMap<&str, &Value>,
+    ) {
+        let scheduler = match self.find_scheduler(&options) {
+            Some(s) => s,
+            None => {
+                send_error(&ctx, command, "Invalid message id").await;
+                return;
+            }
+        };
+        let role = RoleId::from_str(options.get("role").unwrap().as_str().unwrap())
+            .expect("Error parsing role");
+        let mut session = CommandSession::new(&ctx, command);
+        // Checked before counting the role, which fetches every member of the server
+        if !scheduler.can_respond_for(&session, role).await {
+            send_error(&ctx, command, &Scheduler::respond_for_denied(role)).await;
+            return;
+        }
+        let size = match options.get("count") {
+            Some(count) => count.as_u64().expect("Count has incorrect type") as usize,
+            None => {
+                let guild = command.guild_id.expect("Cannot get guild");
+                match count_role_members(&ctx, guild, role).await {
+                    Ok(size) => size,
+                    Err(e) => {
+                        error!("Cannot count role members: {}", e);
+                        send_error(
+                            &ctx,
+                            command,
+                            "Cannot get role size; please specify a count",
+                        )
+                        .await;
+                        return;
+                    }
+                }
+            }
+        };
+        scheduler
+            .get_response(
+                &ctx,
+                &mut session,
+                ResponseType::Block(role, size),
+                &self.profiles,
+            )
+            .await;
+    }
+
+    async fn handle_get_response(
+        &self,
+        ctx: Context,
+        component: &MessageComponentInteraction,
+        resp_type: ResponseType,
+    ) {
+        let scheduler = match resp_type {
+            ResponseType::Normal => self
+                .get_scheduler(component.message.id)
+                .expect("Cannot find scheduler"),
+            ResponseType::Blackout => self.get_followup_scheduler(component),
+            ResponseType::Block(..) => panic!("Role blocks are submitted via command"),
+            ResponseType::Confirm => panic!("Confirmations are opened with the response button"),
+        };
+        if resp_type == ResponseType::Blackout && scheduler.requires_blackout_reason() {
+            let modal = match scheduler.ask_blackout_reason(&ctx, component).await {
+                Some(modal) => modal,
+                None => return,
+            };
+            let mut session = ModalSession::new(&ctx, &modal);
+            scheduler
+                .get_response(&ctx, &mut session, resp_type, &self.profiles)
+                .await;
+            return;
+        }
+        let mut session = ComponentSession::new(&ctx, component);
+        scheduler
+            .get_response(&ctx, &mut session, resp_type, &self.profiles)
+            .await
+    }
+
+    /// Looks up the scheduler for a button on one of its ephemeral "Show details" messages
+    fn get_followup_scheduler(&self, component: &MessageComponentInteraction) -> Arc<Scheduler> {
+        let message_id = component
+            .message
+            .message_reference
+            .as_ref()
+            .expect("Cannot find message for DM")
+            .message_id
+            .unwrap();
+        self.get_scheduler(message_id)
+            .expect("Cannot find scheduler")
+    }
+
+    async fn handle_send_reminder(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_followup_scheduler(component)
+            .send_reminder(&ctx, component, &self.notifier)
+            .await;
+    }
+
+    async fn handle_create_threads(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_followup_scheduler(component)
+            .create_threads(&ctx, component)
+            .await;
+    }
+
+    async fn handle_prune(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_followup_scheduler(component)
+            .prune(&ctx, component)
+            .await;
+    }
+
+    async fn handle_create_event(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_followup_scheduler(component)
+            .create_event(&ctx, component)
+            .await;
+    }
+
+    async fn handle_edit_times(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_scheduler(component.message.id)
+            .expect("Cannot find scheduler")
+            .edit_times(&ctx, component)
+            .await;
+    }
+
+    async fn handle_edit_notes(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_followup_scheduler(component)
+            .edit_notes(&ctx, component)
+            .await;
+    }
+
+    async fn handle_set_locked(
+        &self,
+        ctx: Context,
+        component: &MessageComponentInteraction,
+        locked: bool,
+    ) {
+        self.get_followup_scheduler(component)
+            .handle_set_locked(&ctx, component, locked)
+            .await;
+    }
+
+    async fn handle_set_closed_to_new(
+        &self,
+        ctx: Context,
+        component: &MessageComponentInteraction,
+        closed: bool,
+    ) {
+        self.get_followup_scheduler(component)
+            .handle_set_closed_to_new(&ctx, component, closed)
+            .await;
+    }
+
+    async fn handle_set_pinned(
+        &self,
+        ctx: Context,
+        component: &MessageComponentInteraction,
+        pinned: bool,
+    ) {
+        self.get_followup_scheduler(component)
+            .handle_set_pinned(&ctx, component, pinned)
+            .await;
+    }
+
+    async fn handle_set_public_details(
+        &self,
+        ctx: Context,
+        component: &MessageComponentInteraction,
+        enabled: bool,
+    ) {
+        self.get_followup_scheduler(component)
+            .set_public_details(&ctx, component, enabled)
+            .await;
+    }
+
+    #[cfg(feature = "heatmap")]
+    async fn handle_show_chart(&self, ctx: Context, component: &MessageComponentInteraction) {
+        self.get_followup_scheduler(component)
+            .show_chart(&ctx, component)
+            .await;
+    }
+
+    async fn handle_show_details(&self, ctx: Context, component: &MessageComponentInteraction) {
+        let message_id = component.message.id;
+        let scheduler = self
+            .get_scheduler(message_id)
+            .expect("Cannot find scheduler");
+        scheduler.show_details(&ctx, component).await;
+    }
+
+    /// Sends each owner who opted in a DM summarizing their open schedulers, once a day
+    fn start_daily_summaries(&self) {
+        let registry = self.registry.clone();
+        let notifier = self.notifier.clone();
+        let profiles = self.profiles.clone();
+        tokio::spawn(async move {
+            // The first summaries are sent a day after startup rather than on every restart
+            let start = tokio::time::Instant::now() + SUMMARY_INTERVAL;
+            let mut interval = tokio::time::interval_at(start, SUMMARY_INTERVAL);
+            loop {
+                interval.tick().await;
+                send_daily_summaries(&registry, &notifier, &profiles);
+            }
+        });
+    }
+
+    /// Blacks out dates that have passed on the schedulers that opted in
+    fn start_past_date_blackouts(&self, ctx: &Context) {
+        let registry = self.registry.clone();
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PAST_DATES_INTERVAL);
+            loop {
+                interval.tick().await;
+                let today = Local::now().date_naive();
+                for scheduler in registry.iter() {
+                    scheduler.blackout_past_dates(&ctx, today).await;
+                }
+            }
+        });
+    }
+
+    async fn do_initialization(&self, ctx: &Context) {
+        self.notifier.start(ctx.http.clone());
+        self.start_daily_summaries();
+        self.start_past_date_blackouts(ctx);
+        // Posts that were due while the bot wasn't running are posted straight away
+        for (id, post_at) in self.pending.times() {
+            self.schedule_post(ctx, id, post_at);
+        }
+
+        info!("registering");
+        Command::create_global_application_command(&ctx, |command| {
+            command
+                .name("schedule")
+                .description("scheduler")
+                .create_option(|o| {
+                    o.name("create")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Create a scheduler")
+                        .create_sub_option(|o| {
+                            o.name("description")
+                                .description("event description")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("group")
+                                .description("player group")
+                                .kind(CommandOptionType::Role)
+                        });
+                    add_date_options(o)
+                        .create_sub_option(|o| {
+                            o.name("quorum")
+                                .description("responses needed on a date")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(1)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("seed")
+                                .description(
+                                    "message id of a previous scheduler to copy responses from",
+                                )
+                                .kind(CommandOptionType::String)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("show_blackout")
+                                .description(
+                                    "list blackout dates when responding instead of hiding them",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("max_responders")
+                                .description("maximum number of users who can respond")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(1)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("pin")
+                                .description("pin the scheduler message in its channel")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("auto_bump")
+                                .description(
+                                    "repost the scheduler after this many messages are posted below it",
+                                )
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(5)
+                                .max_int_value(1000)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("auto_bump_cooldown")
+                                .description("minimum minutes between automatic reposts")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(10)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("reaction_nudge")
+                                .description("DM users who react to the message that reactions aren't counted")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("highlight")
+                                .description("how to mark the most popular dates")
+                                .kind(CommandOptionType::String)
+                                .add_string_choice("Underline", "underline")
+                                .add_string_choice("Bold", "bold")
+                                .add_string_choice("Star", "star")
+                                .add_string_choice("None", "none")
+                        })
+                        .create_sub_option(|o| {
+                            o.name("mark_soonest")
+                                .description("mark the earliest date that meets the quorum with ⏩")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("auto_prune")
+                                .description(
+                                    "remove responses from users who leave the server (needs member events)",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("notes")
+                                .description(
+                                    "details shown above the results (use \\n for line breaks)",
+                                )
+                                .kind(CommandOptionType::String)
+                                .max_length(MAX_NOTES as u16)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("hide_results")
+                                .description(
+                                    "only show the number of responses to anyone but the owner",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("bars")
+                                .description("draw a bar next to each date's count")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("weekday_averages")
+                                .description(
+                                    "show the average availability on each weekday in the details",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("open_details")
+                                .description(
+                                    "show everyone who responded on each date in the details",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("opens_at")
+                                .description(
+                                    "time to start accepting responses, as YYYY-MM-DD HH:MM",
+                                )
+                                .kind(CommandOptionType::String)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("delete")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Delete a scheduler and its messages")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("settings")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Change the settings of an existing scheduler")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("blackout_reason")
+                                .description("ask for a reason when setting blackout dates")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("coverage_dates")
+                                .description("number of dates to suggest for reaching the most people")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(0)
+                                .max_int_value(MAX_COVERAGE_DATES)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("target")
+                                .description("number of available people to aim for (0 for none)")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(0)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("leading")
+                                .description("show the dates with the most responses so far")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("month_headers")
+                                .description("head the results of each month in polls over several months")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("shortlist")
+                                .description("number of leading dates to ask for confirmation on (0 to stop)")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(0)
+                                .max_int_value(25)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("time_ranges")
+                                .description("let responders give the times they're available")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("blackout_past")
+                                .description("automatically black out dates once they have passed")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("role_loss")
+                                .description("what to do when a responder loses the group role")
+                                .kind(CommandOptionType::String)
+                                .add_string_choice("Keep their response", "keep")
+                                .add_string_choice("Remove their response", "remove")
+                                .add_string_choice("Keep it and notify the owner", "notify")
+                        })
+                        .create_sub_option(|o| {
+                            o.name("tie_break")
+                                .description("which of the dates tied for the most responses to highlight")
+                                .kind(CommandOptionType::String)
+                                .add_string_choice("All of them", "all")
+                                .add_string_choice("Earliest", "earliest")
+                                .add_string_choice("Latest", "latest")
+                                .add_string_choice("Prefer Monday", "Mon")
+                                .add_string_choice("Prefer Tuesday", "Tue")
+                                .add_string_choice("Prefer Wednesday", "Wed")
+                                .add_string_choice("Prefer Thursday", "Thu")
+                                .add_string_choice("Prefer Friday", "Fri")
+                                .add_string_choice("Prefer Saturday", "Sat")
+                                .add_string_choice("Prefer Sunday", "Sun")
+                        })
+                        .create_sub_option(|o| {
+                            o.name("preselect_all")
+                                .description("start new responses with every date selected")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("add_organizer")
+                                .description("responder who doesn't take up a spot")
+                                .kind(CommandOptionType::User)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("remove_organizer")
+                                .description("organizer who should take up a spot again")
+                                .kind(CommandOptionType::User)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("visibility")
+                                .description("who can see the results and who is available")
+                                .kind(CommandOptionType::String)
+                                .add_string_choice("Public: everyone sees the results", "public")
+                                .add_string_choice("Open: everyone also sees who is available", "open")
+                                .add_string_choice("Private: only the owner sees the results", "private")
+                                .add_string_choice("Blind: results are shown after responding", "blind")
+                        })
+                })
+                .create_option(|o| {
+                    o.name("transfer")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Transfer all of a user's schedulers to another user")
+                        .create_sub_option(|o| {
+                            o.name("from")
+                                .description("current owner")
+                                .kind(CommandOptionType::User)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("to")
+                                .description("new owner")
+                                .kind(CommandOptionType::User)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("export")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Download a scheduler's saved state to import elsewhere")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("import")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Recreate an exported scheduler in this channel")
+                        .create_sub_option(|o| {
+                            o.name("file")
+                                .description("file from /schedule export")
+                                .kind(CommandOptionType::Attachment)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("nudge")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Ask responders with old responses to check and resubmit them")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("days")
+                                .description("nudge responses last updated more than this many days ago")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(1)
+                                .max_int_value(365)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("clear_after")
+                                .description("remove responses that aren't resubmitted within this many hours")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(1)
+                                .max_int_value(24 * 14)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("stats")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Show how many of this server's schedulers you've responded to")
+                })
+                .create_option(|o| {
+                    o.name("recover")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Post a new message for a scheduler whose message was lost")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("force")
+                                .description("replace the message even if it still exists")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("profile")
+                        .kind(CommandOptionType::SubCommand)
+                        .description(
+                            "Set the dates you are generally free, to apply when responding",
+                        )
+                        .create_sub_option(|o| {
+                            o.name("days")
+                                .description("weekdays you are free on")
+                                .kind(CommandOptionType::String)
+                                .add_string_choice("Saturday + Sunday", "Sat+Sun")
+                                .add_string_choice("Sunday", "Sun")
+                                .add_string_choice("Monday", "Mon")
+                                .add_string_choice("Tuesday", "Tue")
+                                .add_string_choice("Wednesday", "Wed")
+                                .add_string_choice("Thursday", "Thu")
+                                .add_string_choice("Friday", "Fri")
+                                .add_string_choice("Saturday", "Sat")
+                        })
+                        .create_sub_option(|o| {
+                            o.name("text_responses")
+                                .description(
+                                    "respond by replying to a list of dates in DMs instead of using menus",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("daily_summary")
+                                .description("get a daily DM summarizing your open schedulers")
+                                .kind(CommandOptionType::Boolean)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("clear")
+                                .description(
+                                    "delete your profile before applying any other options",
+                                )
+                                .kind(CommandOptionType::Boolean)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("merge")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Merge one scheduler's responses into another")
+                        .create_sub_option(|o| {
+                            o.name("source")
+                                .description("message id of the scheduler to merge and delete")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("target")
+                                .description("message id of the scheduler to merge into")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("block")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Respond on behalf of a role")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("role")
+                                .description("role to respond for")
+                                .kind(CommandOptionType::Role)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("count")
+                                .description("number of responses to count (default: role size)")
+                                .kind(CommandOptionType::Integer)
+                                .min_int_value(1)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("post_later")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Create a scheduler to be posted in this channel later")
+                        .create_sub_option(|o| {
+                            o.name("description")
+                                .description("event description")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("post_at")
+                                .description("time to post the scheduler, as YYYY-MM-DD HH:MM")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("group")
+                                .description("player group")
+                                .kind(CommandOptionType::Role)
+                        });
+                    add_date_options(o)
+                })
+                .create_option(|o| {
+                    o.name("pending")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("List your schedulers waiting to be posted")
+                        .create_sub_option(|o| {
+                            o.name("cancel")
+                                .description("id of a pending post to cancel")
+                                .kind(CommandOptionType::String)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("preview")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("List the dates a scheduler would include, without creating it");
+                    add_date_options(o)
+                })
+                .create_option(|o| {
+                    o.name("repost")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Repost a scheduler message")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("bump")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Repost a scheduler at the bottom of its channel")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|o| {
+                    o.name("results")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Show a summary of a scheduler's results")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|o| {
+                            o.name("format")
+                                .description("how to show the results")
+                                .kind(CommandOptionType::String)
+                                .add_string_choice("Summary", "summary")
+                                .add_string_choice("Markdown table", "table")
+                        })
+                });
+            #[cfg(feature = "dev-tools")]
+            if self.dev_user.is_some() {
+                command.create_option(|o| {
+                    o.name("dev_editor")
+                        .kind(CommandOptionType::SubCommand)
+                        .description("Run the response editor with random dates (developer only)")
+                        .create_sub_option(|o| {
+                            o.name("id")
+                                .description("message id")
+                                .kind(CommandOptionType::String)
+                                .required(true)
+                        })
+                });
+            }
+            command
+        })
+        .await
+        .expect("Cannot create command");
+
+        for scheduler in self.registry.iter() {
+            scheduler.schedule_opening(ctx);
+        }
+
+        if self.refresh {
+            for scheduler in self.registry.iter() {
+                scheduler.refresh_channel_names(ctx).await;
+                scheduler.update_messages(ctx).await;
+            }
+        }
+    }
+}
+
+#[async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         match interaction {
@@ -355,10 +2759,30 @@ impl EventHandler for Handler {
                         self.handle_get_response(ctx, &component, ResponseType::Blackout)
                             .await
                     }
+                    "times" => self.handle_edit_times(ctx, &component).await,
                     "details" => self.handle_show_details(ctx, &component).await,
+                    "remind" => self.handle_send_reminder(ctx, &component).await,
+                    "threads" => self.handle_create_threads(ctx, &component).await,
+                    "prune" => self.handle_prune(ctx, &component).await,
+                    "notes" => self.handle_edit_notes(ctx, &component).await,
+                    "event" => self.handle_create_event(ctx, &component).await,
+                    "lock" => self.handle_set_locked(ctx, &component, true).await,
+                    "unlock" => self.handle_set_locked(ctx, &component, false).await,
+                    "pin" => self.handle_set_pinned(ctx, &component, true).await,
+                    "unpin" => self.handle_set_pinned(ctx, &component, false).await,
+                    "close_new" => self.handle_set_closed_to_new(ctx, &component, true).await,
+                    "open_new" => self.handle_set_closed_to_new(ctx, &component, false).await,
+                    "public_details" => self.handle_set_public_details(ctx, &component, true).await,
+                    "private_details" => {
+                        self.handle_set_public_details(ctx, &component, false).await
+                    }
+                    #[cfg(feature = "heatmap")]
+                    "chart" => self.handle_show_chart(ctx, &component).await,
                     _ => (),
                 }
             }
+            // Handled by the collector waiting for them
+            Interaction::ModalSubmit(_) => (),
             _ => panic!("Unexpected interaction: {:?}", interaction),
         }
     }
@@ -370,6 +2794,76 @@ impl EventHandler for Handler {
             .await;
     }
 
+    async fn message(&self, ctx: Context, message: Message) {
+        // Also skips the bot's own reposts
+        if message.author.bot {
+            return;
+        }
+        for scheduler in self.registry.iter() {
+            if scheduler.get_latest_channel() == message.channel_id && scheduler.message_posted() {
+                info!("auto-bumping scheduler: {}", scheduler.get_id());
+                if let Err(e) = self.bump(&ctx, &scheduler).await {
+                    error!("Cannot bump scheduler: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn channel_update(&self, _ctx: Context, _old: Option<Channel>, new: Channel) {
+        if let Channel::Guild(channel) = new {
+            for scheduler in self.registry.iter() {
+                scheduler.channel_renamed(channel.id, &channel.name);
+            }
+        }
+    }
+
+    // Threads and forum posts are renamed separately from other channels
+    async fn thread_update(&self, _ctx: Context, thread: GuildChannel) {
+        for scheduler in self.registry.iter() {
+            scheduler.channel_renamed(thread.id, &thread.name);
+        }
+    }
+
+    async fn guild_member_update(&self, ctx: Context, _old: Option<Member>, new: Member) {
+        for scheduler in self.registry.iter() {
+            if in_guild(&ctx, scheduler.get_channel(), new.guild_id).await {
+                scheduler.member_updated(&ctx, self.notifier.clone(), &new);
+            }
+        }
+    }
+
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        _member: Option<Member>,
+    ) {
+        for scheduler in self.registry.iter() {
+            if in_guild(&ctx, scheduler.get_channel(), guild_id).await {
+                scheduler.member_left(&ctx, user.id).await;
+            }
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let (user_id, guild_id) = match (reaction.user_id, reaction.guild_id) {
+            (Some(u), Some(g)) => (u, g),
+            _ => return,
+        };
+        if user_id == ctx.cache.current_user_id() {
+            return;
+        }
+        let scheduler = match self.get_scheduler(reaction.message_id) {
+            Some(s) => s,
+            None => return,
+        };
+        if let Some(notification) = scheduler.get_reaction_nudge(user_id, guild_id) {
+            info!("nudging {} to respond", user_id);
+            self.notifier.send(notification);
+        }
+    }
+
     async fn message_delete(
         &self,
         ctx: Context,
@@ -377,19 +2871,13 @@ impl EventHandler for Handler {
         deleted_message_id: MessageId,
         _guild_id: Option<GuildId>,
     ) {
-        if let Some(scheduler) = self.schedulers.remove(&deleted_message_id) {
+        if let Some(scheduler) = self.registry.remove(deleted_message_id) {
             info!("scheduler message deleted: {}", deleted_message_id);
             delete_file(&deleted_message_id);
-            if let Some(repost_id) = scheduler.val().get_repost() {
-                self.reposts.remove(&repost_id).unwrap();
-                scheduler.val().delete_repost(&ctx).await;
-            }
-        } else if let Some(id) = self.reposts.remove(&deleted_message_id) {
+            scheduler.delete_repost(&ctx).await;
+        } else if let Some(scheduler) = self.registry.remove_repost(deleted_message_id) {
             info!("scheduler repost deleted: {}", deleted_message_id);
-            let scheduler = self
-                .get_scheduler(*id.val())
-                .expect("Cannot find scheduler");
-            scheduler.val().repost(&ctx, None).await;
+            scheduler.repost(&ctx, None).await;
         }
     }
 }
@@ -399,24 +2887,91 @@ impl EventHandler for Handler {
 struct Cli {
     #[clap(long, action)]
     refresh: bool,
+    /// Log each step of response editing sessions and other interactions
+    #[clap(long, action)]
+    verbose: bool,
+    /// Receive member events, used to remove responses from users who leave or lose the group
+    /// role; requires the privileged Server Members intent
+    #[clap(long, action)]
+    member_events: bool,
+    /// Write a combined copy of all saved schedulers to this directory and exit
+    #[clap(long, value_name = "DIR")]
+    backup: Option<PathBuf>,
+    /// Recreate the saved scheduler files from a backup and exit
+    #[clap(long, value_name = "FILE", conflicts_with = "backup")]
+    restore: Option<PathBuf>,
+    /// Check all saved schedulers for inconsistencies, log the problems found, and exit
+    #[clap(long, action, conflicts_with_all = &["backup", "restore"])]
+    validate: bool,
+    /// Repair the problems found by --validate
+    #[clap(long, action, requires = "validate")]
+    fix: bool,
+    /// Maximum reminders and pings sent per minute across all schedulers
+    #[clap(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    notify_rate: u32,
+    /// Number of reminders and pings that can be sent at once before throttling
+    #[clap(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    notify_burst: u32,
+    /// Minimum seconds between edits of the same scheduler's messages; changes made in between
+    /// are shown together
+    #[clap(long, default_value_t = 2)]
+    edit_interval: u64,
+    /// Minimum seconds between submissions of each user's response to a scheduler
+    #[clap(long, default_value_t = 3)]
+    submit_cooldown: u64,
+    /// User id allowed to run the developer commands
+    #[cfg(feature = "dev-tools")]
+    #[clap(long, value_name = "USER_ID")]
+    dev_user: Option<u64>,
 }
 
 #[tokio::main]
 async fn main() {
-    env_logger::Builder::new()
-        .target(env_logger::Target::Stdout)
-        .filter(Some("scheduler"), log::LevelFilter::Info)
-        .init();
     let cli = Cli::parse();
-
+    let mut logger = env_logger::Builder::new();
+    logger.target(env_logger::Target::Stdout);
+    if cli.verbose {
+        // Span entries and exits are logged under this target when no tracing subscriber is set
+        logger
+            .filter(Some("scheduler"), log::LevelFilter::Debug)
+            .filter(Some("tracing::span"), log::LevelFilter::Trace);
+    } else {
+        logger.filter(Some("scheduler"), log::LevelFilter::Info);
+    }
+    logger.init();
     dotenv().ok();
+    encryption::init(env::var("DATA_KEY").ok().as_deref()).expect("Cannot set encryption key");
+    if let Some(dir) = &cli.backup {
+        create_data_dir();
+        backup(dir);
+        return;
+    }
+    if let Some(path) = &cli.restore {
+        create_data_dir();
+        restore(path);
+        return;
+    }
+
+    if cli.validate {
+        create_data_dir();
+        validate(cli.fix).await;
+        return;
+    }
     // Configure the client with your Discord bot token in the environment.
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
 
     // Build our client.
-    let intents = GatewayIntents::GUILD_MESSAGES;
+    let mut intents = GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
+        | GatewayIntents::DIRECT_MESSAGES;
+    if cli.member_events {
+        intents |= GatewayIntents::GUILD_MEMBERS;
+    }
+    scheduler::set_edit_interval(std::time::Duration::from_secs(cli.edit_interval));
+    scheduler::set_submit_cooldown(std::time::Duration::from_secs(cli.submit_cooldown));
     let mut client = Client::builder(token, intents)
-        .event_handler(Handler::new(cli.refresh))
+        .event_handler(Handler::new(&cli))
         .await
         .expect("Error creating client");
 
@@ -7,11 +7,12 @@ use serenity::model::channel::Message;
 use serenity::model::id::{ChannelId, MessageId};
 
 /// Lightweight version of [`serenity::model::channel::Message`] that only supports
-/// [`edit`](MessageShim::edit) and [`delete`](MessageShim::delete)
+/// [`edit`](MessageShim::edit), [`delete`](MessageShim::delete), [`pin`](MessageShim::pin), and
+/// [`unpin`](MessageShim::unpin)
 #[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct MessageShim {
     pub message_id: MessageId,
-    channel_id: ChannelId,
+    pub channel_id: ChannelId,
 }
 
 impl MessageShim {
@@ -41,6 +42,20 @@ impl MessageShim {
             .delete_message(&cache_http.http(), self.message_id)
             .await
     }
+
+    /// See [`serenity::model::channel::Message::pin`]
+    pub async fn pin(&self, cache_http: impl CacheHttp) -> serenity::Result<()> {
+        self.channel_id
+            .pin(cache_http.http(), self.message_id)
+            .await
+    }
+
+    /// See [`serenity::model::channel::Message::unpin`]
+    pub async fn unpin(&self, cache_http: impl CacheHttp) -> serenity::Result<()> {
+        self.channel_id
+            .unpin(cache_http.http(), self.message_id)
+            .await
+    }
 }
 
 impl From<Message> for MessageShim {
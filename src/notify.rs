@@ -0,0 +1,109 @@
+use log::{error, info};
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, MessageId, RoleId, UserId};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Recipient {
+    Channel(ChannelId),
+    /// Sent as a DM
+    User(UserId),
+}
+
+/// A message sent by the bot outside of an interaction response (reminders, pings, etc.)
+#[derive(Debug)]
+pub struct Notification {
+    pub recipient: Recipient,
+    /// Only used for channel notifications
+    pub reply_to: Option<MessageId>,
+    pub content: String,
+    pub mention_role: Option<RoleId>,
+}
+
+/// Shared queue that dispatches [`Notification`]s in order, throttled by a token bucket so that
+/// bursts from many schedulers don't run into Discord rate limits
+pub struct Notifier {
+    sender: UnboundedSender<Notification>,
+    receiver: Mutex<Option<UnboundedReceiver<Notification>>>,
+    rate: f64,
+    burst: f64,
+}
+
+impl Notifier {
+    /// `per_minute` is the sustained number of notifications sent per minute and `burst` is the
+    /// number that can be sent back-to-back before throttling kicks in
+    pub fn new(per_minute: u32, burst: u32) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            rate: per_minute.max(1) as f64 / 60.0,
+            burst: burst.max(1) as f64,
+        }
+    }
+
+    /// Starts the dispatch task; only the first call has any effect
+    pub fn start(&self, http: Arc<Http>) {
+        let receiver = match self.receiver.lock().unwrap().take() {
+            Some(r) => r,
+            None => return,
+        };
+        tokio::spawn(dispatch(http, receiver, self.rate, self.burst));
+    }
+
+    pub fn send(&self, notification: Notification) {
+        if let Err(e) = self.sender.send(notification) {
+            error!("Cannot queue notification: {}", e.0.content);
+        }
+    }
+}
+
+async fn dispatch(
+    http: Arc<Http>,
+    mut receiver: UnboundedReceiver<Notification>,
+    rate: f64,
+    burst: f64,
+) {
+    let mut tokens = burst;
+    let mut last = Instant::now();
+    while let Some(notification) = receiver.recv().await {
+        let now = Instant::now();
+        tokens = (tokens + (now - last).as_secs_f64() * rate).min(burst);
+        last = now;
+        if tokens < 1.0 {
+            let delay = Duration::from_secs_f64((1.0 - tokens) / rate);
+            info!(
+                "throttling notification to {:?} for {:?}",
+                notification.recipient, delay
+            );
+            tokio::time::sleep(delay).await;
+            tokens = 1.0;
+            last = Instant::now();
+        }
+        tokens -= 1.0;
+
+        if let Err(e) = send(&http, &notification).await {
+            error!("Cannot send notification: {}", e);
+        }
+    }
+}
+
+async fn send(http: &Http, notification: &Notification) -> serenity::Result<()> {
+    let (channel_id, reply_to) = match notification.recipient {
+        Recipient::Channel(channel_id) => (channel_id, notification.reply_to),
+        Recipient::User(user_id) => (user_id.create_dm_channel(http).await?.id, None),
+    };
+    channel_id
+        .send_message(http, |m| {
+            if let Some(reply_to) = reply_to {
+                m.reference_message((channel_id, reply_to));
+            }
+            m.content(&notification.content)
+                .allowed_mentions(|am| am.roles(notification.mention_role))
+        })
+        .await?;
+    Ok(())
+}
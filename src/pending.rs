@@ -0,0 +1,105 @@
+use crate::encryption;
+use crate::error::SchedulerError;
+use crate::scheduler::SchedulerBuilder;
+
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, UserId};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+// Kept outside the data directory, which only holds schedulers
+const PENDING_FILE: &str = "pending.json";
+
+/// A scheduler that hasn't been posted yet. Its dates are only generated once it is posted.
+#[derive(Serialize, Deserialize)]
+pub struct PendingPost {
+    pub channel: ChannelId,
+    pub post_at: DateTime<Utc>,
+    pub builder: SchedulerBuilder,
+}
+
+/// Schedulers waiting to be posted, keyed by the id of the command that created them
+#[derive(Default)]
+pub struct PendingPosts {
+    posts: RwLock<HashMap<u64, PendingPost>>,
+}
+
+impl PendingPosts {
+    pub fn load() -> Self {
+        let posts: HashMap<u64, PendingPost> = match std::fs::read(PENDING_FILE) {
+            Ok(data) => {
+                let data = encryption::decrypt(data).expect("Cannot decrypt pending posts");
+                serde_json::from_slice(&data).expect("Cannot parse pending posts")
+            }
+            Err(_) => Default::default(),
+        };
+        info!("{} pending posts loaded", posts.len());
+        Self {
+            posts: posts.into(),
+        }
+    }
+
+    pub fn insert(&self, id: u64, post: PendingPost) -> Result<(), SchedulerError> {
+        let mut posts = self.posts.write().unwrap();
+        posts.insert(id, post);
+        Self::save(&posts)
+    }
+
+    /// Removes a post so that it can be posted. Returns `None` if it was cancelled.
+    pub fn take(&self, id: u64) -> Result<Option<PendingPost>, SchedulerError> {
+        let mut posts = self.posts.write().unwrap();
+        let post = posts.remove(&id);
+        Self::save(&posts)?;
+        Ok(post)
+    }
+
+    /// Cancels a post if `user` owns it, returning whether it was found
+    pub fn cancel(&self, id: u64, user: UserId) -> Result<bool, SchedulerError> {
+        let mut posts = self.posts.write().unwrap();
+        if posts.get(&id).is_none_or(|p| p.builder.get_owner() != user) {
+            return Ok(false);
+        }
+        posts.remove(&id);
+        Self::save(&posts)?;
+        Ok(true)
+    }
+
+    /// Ids and times of every pending post
+    pub fn times(&self) -> Vec<(u64, DateTime<Utc>)> {
+        let posts = self.posts.read().unwrap();
+        posts.iter().map(|(id, p)| (*id, p.post_at)).collect()
+    }
+
+    /// Descriptions of the posts `user` owns, in the order they will be posted
+    pub fn list(&self, user: UserId) -> Vec<String> {
+        let posts = self.posts.read().unwrap();
+        let mut owned: Vec<_> = posts
+            .iter()
+            .filter(|(_, p)| p.builder.get_owner() == user)
+            .collect();
+        owned.sort_by_key(|(_, p)| p.post_at);
+        owned
+            .into_iter()
+            .map(|(id, p)| {
+                format!(
+                    "`{}`: **{}** in <#{}> <t:{}:f>",
+                    id,
+                    p.builder.get_title(),
+                    p.channel,
+                    p.post_at.timestamp()
+                )
+            })
+            .collect()
+    }
+
+    // Written while still holding the lock so that concurrent changes are saved in order
+    fn save(posts: &HashMap<u64, PendingPost>) -> Result<(), SchedulerError> {
+        let temp_path = format!("{}.tmp", PENDING_FILE);
+        let data = encryption::encrypt(serde_json::to_vec(posts)?)?;
+        std::fs::write(&temp_path, data)?;
+        std::fs::rename(temp_path, PENDING_FILE)?;
+        Ok(())
+    }
+}
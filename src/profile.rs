@@ -0,0 +1,85 @@
+use crate::encryption;
+use crate::error::SchedulerError;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::UserId;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+// Kept outside the data directory, which only holds schedulers
+const PROFILES_FILE: &str = "profiles.json";
+
+/// Dates a user is generally free, which can be applied to their response to any scheduler, and
+/// other preferences that apply to every scheduler
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub dates: HashSet<NaiveDate>,
+    /// Matches every date on these weekdays
+    #[serde(default)]
+    pub weekdays: HashSet<Weekday>,
+    /// Respond by replying to a list of dates in DMs instead of using the response editor
+    #[serde(default)]
+    pub text_responses: bool,
+    /// Receive a daily DM summarizing the open schedulers the user owns
+    #[serde(default)]
+    pub daily_summary: bool,
+}
+
+impl Profile {
+    pub fn matches(&self, date: &NaiveDate) -> bool {
+        self.dates.contains(date) || self.weekdays.contains(&date.weekday())
+    }
+
+    pub fn has_dates(&self) -> bool {
+        !self.dates.is_empty() || !self.weekdays.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.has_dates() && !self.text_responses && !self.daily_summary
+    }
+}
+
+/// Saved profiles of every user, shared between all schedulers
+#[derive(Default)]
+pub struct Profiles {
+    profiles: RwLock<HashMap<UserId, Profile>>,
+}
+
+impl Profiles {
+    pub fn load() -> Self {
+        let profiles: HashMap<UserId, Profile> = match std::fs::read(PROFILES_FILE) {
+            Ok(data) => {
+                let data = encryption::decrypt(data).expect("Cannot decrypt profiles");
+                serde_json::from_slice(&data).expect("Cannot parse profiles")
+            }
+            Err(_) => Default::default(),
+        };
+        info!("{} profiles loaded", profiles.len());
+        Self {
+            profiles: profiles.into(),
+        }
+    }
+
+    pub fn get(&self, user: UserId) -> Option<Profile> {
+        self.profiles.read().unwrap().get(&user).cloned()
+    }
+
+    /// Replaces a user's profile, removing it if it's empty
+    pub fn set(&self, user: UserId, profile: Profile) -> Result<(), SchedulerError> {
+        let mut profiles = self.profiles.write().unwrap();
+        if profile.is_empty() {
+            profiles.remove(&user);
+        } else {
+            profiles.insert(user, profile);
+        }
+        // Written while still holding the lock so that concurrent changes are saved in order
+        let temp_path = format!("{}.tmp", PROFILES_FILE);
+        let data = encryption::encrypt(serde_json::to_vec(&*profiles)?)?;
+        std::fs::write(&temp_path, data)?;
+        std::fs::rename(temp_path, PROFILES_FILE)?;
+        Ok(())
+    }
+}
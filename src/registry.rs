@@ -0,0 +1,89 @@
+use crate::scheduler::Scheduler;
+
+use lockfree::map::Map;
+use log::info;
+use serenity::model::id::MessageId;
+use std::sync::Arc;
+
+/// All loaded schedulers, keyed by the id of their original message.
+///
+/// Schedulers are shared as `Arc`s so that handlers can keep using one across awaits without
+/// holding a guard on the map, and all of their mutable state is behind interior locks. The files
+/// in the data directory are the durable copy: each scheduler saves itself when it changes, and
+/// the registry is only populated from them at startup.
+#[derive(Default)]
+pub struct Registry {
+    schedulers: Map<MessageId, Arc<Scheduler>>,
+    /// Repost message ids, mapped to the id of the scheduler they are a copy of
+    reposts: Map<MessageId, MessageId>,
+}
+
+impl Registry {
+    /// Loads every scheduler saved in the data directory
+    pub fn load_all() -> Self {
+        let registry = Self::default();
+        let mut count = 0;
+        for f in std::fs::read_dir(crate::DATA_DIR).expect("Cannot read data dir") {
+            let path = f.unwrap().path();
+            if let Some((id, s)) = crate::read_file(&path) {
+                registry.insert_with_id(id.into(), s.expect("Cannot parse data"));
+                count += 1;
+            }
+        }
+        info!("{} schedulers loaded", count);
+        registry
+    }
+
+    pub fn insert(&self, scheduler: Scheduler) -> Arc<Scheduler> {
+        self.insert_with_id(scheduler.get_id(), scheduler)
+    }
+
+    fn insert_with_id(&self, id: MessageId, scheduler: Scheduler) -> Arc<Scheduler> {
+        if let Some(repost) = scheduler.get_repost() {
+            self.reposts.insert(repost, id);
+        }
+        let scheduler = Arc::new(scheduler);
+        self.schedulers.insert(id, scheduler.clone());
+        scheduler
+    }
+
+    /// Looks up a scheduler by the id of either its original message or its repost
+    pub fn get(&self, id: MessageId) -> Option<Arc<Scheduler>> {
+        let id = self.reposts.get(&id).map(|g| *g.val()).unwrap_or(id);
+        self.schedulers.get(&id).map(|g| g.val().clone())
+    }
+
+    /// Removes a scheduler by the id of its original message, along with its repost
+    pub fn remove(&self, id: MessageId) -> Option<Arc<Scheduler>> {
+        let removed = self.schedulers.remove(&id)?;
+        let scheduler = removed.val().clone();
+        if let Some(repost) = scheduler.get_repost() {
+            self.reposts.remove(&repost);
+        }
+        Some(scheduler)
+    }
+
+    /// Moves a scheduler from `old_id` to its current id, after its original message is replaced
+    pub fn rekey(&self, old_id: MessageId, scheduler: Arc<Scheduler>) {
+        self.schedulers.remove(&old_id);
+        self.schedulers
+            .insert(scheduler.get_id(), scheduler.clone());
+        if let Some(repost) = scheduler.get_repost() {
+            self.reposts.insert(repost, scheduler.get_id());
+        }
+    }
+
+    pub fn add_repost(&self, repost_id: MessageId, scheduler: &Scheduler) {
+        self.reposts.insert(repost_id, scheduler.get_id());
+    }
+
+    /// Forgets a repost, returning the scheduler it was a copy of
+    pub fn remove_repost(&self, repost_id: MessageId) -> Option<Arc<Scheduler>> {
+        let removed = self.reposts.remove(&repost_id)?;
+        self.get(*removed.val())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Arc<Scheduler>> + '_ {
+        self.schedulers.iter().map(|g| g.val().clone())
+    }
+}
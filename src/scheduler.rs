@@ -1,444 +1,4854 @@
+use crate::error::SchedulerError;
 use crate::message_shim::MessageShim;
+use crate::notify::{Notification, Notifier, Recipient};
+use crate::profile::{Profile, Profiles};
+#[cfg(feature = "dev-tools")]
+use crate::session::EditorAction;
+use crate::session::{ComponentSession, EditorHost, ResponseSession};
+use crate::strings::{weekday_name, Text};
 
-use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Utc, Weekday};
 use chronoutil::DateRule;
 use itertools::Itertools;
-use log::{error, info};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use serenity::builder::{CreateActionRow, CreateButton, CreateComponents, CreateSelectMenu};
 use serenity::client::Context;
-use serenity::model::application::component::ButtonStyle;
+use serenity::http::{Http, HttpError};
+use serenity::model::application::component::{ActionRowComponent, ButtonStyle, InputTextStyle};
 use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::modal::ModalSubmitInteraction;
 use serenity::model::application::interaction::InteractionResponseType;
-use serenity::model::channel::Message;
-use serenity::model::id::{MessageId, RoleId, UserId};
+#[cfg(feature = "heatmap")]
+use serenity::model::channel::AttachmentType;
+use serenity::model::channel::{Channel, ChannelType, Message};
+use serenity::model::guild::{Member, ScheduledEventType};
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, ScheduledEventId, UserId};
 use std::collections::{HashMap, HashSet};
-use std::sync::RwLock;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Instant;
+use tracing::instrument;
 
 // Ephemeral messages can only be edited for a limited time after they are initally created;
 // testing indicates that this limit is 15 minutes
 const RESP_TIMEOUT: std::time::Duration = std::time::Duration::new(60 * 14, 0);
+// Limits the chart size, and keeps the legend within the message length limit
+#[cfg(feature = "heatmap")]
+const MAX_CHART_USERS: usize = 40;
+// Maximum number of per-date discussion threads for a scheduler
+const MAX_THREADS: usize = 5;
+// Number of consecutive failed updates before the owner is notified
+const EDIT_FAILURE_LIMIT: usize = 3;
+// Selecting all dates asks for confirmation when there are more than this many
+const SELECT_ALL_CONFIRM: usize = 10;
+// Maximum length of an embed field value
+const FIELD_LIMIT: usize = 1024;
+// Number of mentions per line when listing all responders in the details
+const MENTIONS_PER_LINE: usize = 50;
+// Select menus can have at most this many options
+const MAX_MENU_OPTIONS: usize = 25;
+// Number of dates listed in a results summary
+const SUMMARY_DATES: usize = 3;
+// Discord error code for a user who isn't a member of the guild
+const UNKNOWN_MEMBER: isize = 10007;
+// Discord error codes for a message or channel that was deleted
+const UNKNOWN_CHANNEL: isize = 10003;
+const UNKNOWN_MESSAGE: isize = 10008;
+// Discord error codes for an interaction whose token has expired
+const UNKNOWN_WEBHOOK: isize = 10015;
+const INVALID_WEBHOOK_TOKEN: isize = 50027;
+// Discord error code for a channel that already has 50 pinned messages
+const MAX_PINS_REACHED: isize = 30003;
+// Most dates that can be suggested together for reaching the most responders, which keeps the
+// number of combinations to check small
+pub const MAX_COVERAGE_DATES: usize = 4;
+// Longest reason that can be given for the blackout dates
+const MAX_BLACKOUT_REASON: u64 = 100;
+// Length limit of the reason a responder gives for declining all of the dates
+const MAX_DECLINE_REASON: u64 = 100;
+// How long to wait for the notes editing dialog to be submitted
+const NOTES_TIMEOUT: std::time::Duration = std::time::Duration::new(60 * 10, 0);
+// Leaves room in the embed description (at most 4096 characters) for the status text
+pub const MAX_NOTES: usize = 4000;
+// How long to wait for a choice in a prompt, such as a confirmation
+pub const CONFIRM_TIMEOUT: std::time::Duration = std::time::Duration::new(60, 0);
+// Minimum time between bumps of the same scheduler
+const BUMP_COOLDOWN: std::time::Duration = std::time::Duration::new(60 * 10, 0);
+// Minutes between automatic bumps, unless configured otherwise
+const AUTO_BUMP_COOLDOWN: u64 = 60;
+// Maximum number of automatic bumps of the same scheduler
+const MAX_AUTO_BUMPS: usize = 10;
+// Maximum length of the bars drawn next to each date's count
+const BAR_WIDTH: usize = 10;
+// Maximum number of embed fields the public detailed results can be split across
+const MAX_RESULT_FIELDS: usize = 4;
+// Dates per results table, so that tables stay readable when pasted elsewhere
+const TABLE_DATES: usize = 7;
+// Longest responder name shown in a results table
+const TABLE_NAME_LENGTH: usize = 20;
+// Losing the group role is only acted on if the role hasn't been given back after this long, so
+// that roles that are briefly removed while being reassigned are ignored
+const ROLE_LOSS_DELAY: std::time::Duration = std::time::Duration::new(60 * 5, 0);
+
+// Minimum time between edits of the same scheduler's messages, which keeps busy schedulers from
+// using up the bot's rate limits
+static EDIT_INTERVAL: OnceLock<std::time::Duration> = OnceLock::new();
+
+// Minimum time between submissions of the same user's response, so that one user can't make the
+// bot save and edit its messages over and over
+static SUBMIT_COOLDOWN: OnceLock<std::time::Duration> = OnceLock::new();
+
+// Shared by every scheduler, so that a token recorded on a response can only match a session of
+// the scheduler that issued it
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(0);
+
+// Length limit of the title, which is used as the embed title
+pub const MAX_TITLE: usize = 256;
+
+// Largest number of weeks that the start of a scheduler can be skipped by (ten years)
+pub const MAX_SKIP_WEEKS: i64 = 520;
+// Longest gap in weeks between successive dates on the same weekday
+pub const MAX_INTERVAL_WEEKS: i64 = 52;
+// Editors opened before responses were locked can still submit for this long afterwards
+const LOCK_GRACE: std::time::Duration = std::time::Duration::new(60 * 2, 0);
+
+/// Sets the minimum time between edits of each scheduler's messages; only the first call has any
+/// effect. Edits aren't limited if this isn't called.
+pub fn set_edit_interval(interval: std::time::Duration) {
+    EDIT_INTERVAL.get_or_init(|| interval);
+}
+
+/// Sets the minimum time between each user's submissions to a scheduler; only the first call has
+/// any effect. Submissions aren't limited if this isn't called.
+pub fn set_submit_cooldown(cooldown: std::time::Duration) {
+    SUBMIT_COOLDOWN.get_or_init(|| cooldown);
+}
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ResponseType {
     Normal,
     Blackout,
+    /// Availability of a whole role, counted as `size` responders
+    Block(RoleId, usize),
+    /// Which of the shortlisted dates a responder can make, in the second round
+    Confirm,
+}
+
+/// A Discord scheduled event created for one of the dates
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct EventLink {
+    guild_id: GuildId,
+    event_id: ScheduledEventId,
+    date: NaiveDate,
+}
+
+/// How the date(s) with the most responses are marked in the results
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightStyle {
+    #[default]
+    Underline,
+    Bold,
+    Star,
+    None,
+}
+
+impl FromStr for HighlightStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "underline" => Ok(HighlightStyle::Underline),
+            "bold" => Ok(HighlightStyle::Bold),
+            "star" => Ok(HighlightStyle::Star),
+            "none" => Ok(HighlightStyle::None),
+            _ => Err(()),
+        }
+    }
+}
+
+impl HighlightStyle {
+    fn format(self, date: impl std::fmt::Display) -> String {
+        match self {
+            HighlightStyle::Underline => format!("__`{}:`__", date),
+            HighlightStyle::Bold => format!("**`{}:`**", date),
+            HighlightStyle::Star => format!("⭐ `{}:`", date),
+            HighlightStyle::None => format!("`{}:`", date),
+        }
+    }
+}
+
+/// Which of several dates tied for the most responses gets the highlight
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TieBreak {
+    /// Highlight all of them
+    #[default]
+    All,
+    Earliest,
+    Latest,
+    /// The earliest on this weekday, or the earliest overall if none of them are
+    Weekday(Weekday),
+}
+
+impl FromStr for TieBreak {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(TieBreak::All),
+            "earliest" => Ok(TieBreak::Earliest),
+            "latest" => Ok(TieBreak::Latest),
+            _ => Weekday::from_str(s).map(TieBreak::Weekday).map_err(|_| ()),
+        }
+    }
+}
+
+impl TieBreak {
+    /// The single date to highlight out of the tied `dates`, which are in order, or `None` to
+    /// highlight all of them
+    fn pick(self, dates: &[NaiveDate]) -> Option<NaiveDate> {
+        match self {
+            TieBreak::All => None,
+            TieBreak::Earliest => dates.first().copied(),
+            TieBreak::Latest => dates.last().copied(),
+            TieBreak::Weekday(weekday) => dates
+                .iter()
+                .find(|d| d.weekday() == weekday)
+                .or_else(|| dates.first())
+                .copied(),
+        }
+    }
+
+    /// The tied `dates`, which are in order, reordered so that those picked come first
+    fn order(self, mut dates: Vec<NaiveDate>) -> Vec<NaiveDate> {
+        let mut ordered = Vec::with_capacity(dates.len());
+        while let Some(date) = self.pick(&dates) {
+            dates.retain(|&d| d != date);
+            ordered.push(date);
+        }
+        ordered.extend(dates);
+        ordered
+    }
+}
+
+/// What happens to the response of a responder who loses the scheduler's group role
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleLossPolicy {
+    /// The response is still counted
+    #[default]
+    Keep,
+    Remove,
+    /// The response is still counted, and the owner is sent a DM about it
+    Notify,
+}
+
+impl FromStr for RoleLossPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(RoleLossPolicy::Keep),
+            "remove" => Ok(RoleLossPolicy::Remove),
+            "notify" => Ok(RoleLossPolicy::Notify),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How much of the results an audience can see; each level includes the ones before it
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Only the number of responses
+    Counts,
+    /// The number of responses on each date
+    Distribution,
+    /// Who is available on each date
+    Users,
+}
+
+/// What each audience can see of a scheduler's results
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultsVisibility {
+    pub owner: Visibility,
+    /// Users other than the owner who have responded
+    pub responders: Visibility,
+    /// Everyone else, including anyone looking at the scheduler message
+    pub everyone: Visibility,
+}
+
+impl ResultsVisibility {
+    pub const PUBLIC: Self = Self {
+        owner: Visibility::Users,
+        responders: Visibility::Distribution,
+        everyone: Visibility::Distribution,
+    };
+    pub const OPEN: Self = Self {
+        owner: Visibility::Users,
+        responders: Visibility::Users,
+        everyone: Visibility::Users,
+    };
+    pub const PRIVATE: Self = Self {
+        owner: Visibility::Users,
+        responders: Visibility::Counts,
+        everyone: Visibility::Counts,
+    };
+    /// Results are only shown to users once they have responded, so that they can't be swayed
+    /// by the other responses
+    pub const BLIND: Self = Self {
+        owner: Visibility::Users,
+        responders: Visibility::Distribution,
+        everyone: Visibility::Counts,
+    };
+
+    /// The preset matching the `hide_results` and `open_details` options
+    fn from_flags(hide_results: bool, open_details: bool) -> Self {
+        if hide_results {
+            Self::PRIVATE
+        } else if open_details {
+            Self::OPEN
+        } else {
+            Self::PUBLIC
+        }
+    }
+}
+
+impl FromStr for ResultsVisibility {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(ResultsVisibility::PUBLIC),
+            "open" => Ok(ResultsVisibility::OPEN),
+            "private" => Ok(ResultsVisibility::PRIVATE),
+            "blind" => Ok(ResultsVisibility::BLIND),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Working state of a response editing session
+struct Editor {
+    response: Response,
+    resp_type: ResponseType,
+    allow_delete: bool,
+    /// Weekdays shown in the date menu, or `None` to show all
+    weekdays: Option<HashSet<Weekday>>,
+    /// Every date was selected to start with, since this is a new response
+    preselected: bool,
+    /// Other responders can be picked to copy or depend on, which is only allowed for users who
+    /// can see who is available on each date
+    show_responders: bool,
+}
+
+/// How a response editing session ended. Only one exists at a time, so the size of `Submit`
+/// doesn't matter.
+#[allow(clippy::large_enum_variant)]
+enum EditorOutcome {
+    Submit(Response),
+    Delete,
+    TimedOut,
+    Cancelled,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Response {
     dates: HashSet<NaiveDate>,
+    /// Available on any (non-blackout) date, without preferring one over another
+    #[serde(default)]
+    flexible: bool,
+    /// Explicitly not available on any of the dates, as opposed to an empty selection
+    #[serde(default)]
+    declined: bool,
+    /// Why the user declined, shown only to the owner
+    #[serde(default)]
+    decline_reason: Option<String>,
+    /// When the response was last submitted; not known for responses saved before this was
+    /// tracked, or seeded from another scheduler
+    #[serde(default)]
+    submitted_at: Option<DateTime<Utc>>,
+    /// Token of the editing session that submitted the response, so that a retried submission
+    /// isn't counted as a new one
+    #[serde(skip)]
+    submission: Option<u64>,
+    /// Only counted on dates that this user has also selected
+    #[serde(default)]
+    depends_on: Option<UserId>,
+    /// Shortlisted dates confirmed in the second round, as opposed to the tentative `dates`
+    #[serde(default)]
+    confirmed: HashSet<NaiveDate>,
+    /// Start and end of the time the user is available on some of their dates, when the
+    /// scheduler asks for times. Kept for dates that are later deselected, but not shown.
+    #[serde(default)]
+    times: HashMap<NaiveDate, (NaiveTime, NaiveTime)>,
+}
+
+impl Response {
+    fn is_available(&self, date: &NaiveDate) -> bool {
+        self.flexible || self.dates.contains(date)
+    }
 }
 
 impl From<HashSet<NaiveDate>> for Response {
     fn from(dates: HashSet<NaiveDate>) -> Self {
-        Response { dates }
+        Response {
+            dates,
+            flexible: false,
+            declined: false,
+            decline_reason: None,
+            submitted_at: None,
+            submission: None,
+            depends_on: None,
+            confirmed: HashSet::new(),
+            times: HashMap::new(),
+        }
+    }
+}
+
+/// Outcome of merging one scheduler's responses into another
+pub struct MergeReport {
+    pub merged: usize,
+    /// Users who responded to both schedulers, and whether the source's response was kept
+    /// because it was newer
+    pub conflicts: Vec<(UserId, bool)>,
+    /// Users whose responses weren't merged because the scheduler reached its maximum number of
+    /// responders
+    pub left_out: Vec<UserId>,
+}
+
+/// Availability submitted on behalf of every member of a role
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RoleBlock {
+    dates: HashSet<NaiveDate>,
+    size: usize,
+}
+
+struct DateTally {
+    date: NaiveDate,
+    users: HashSet<UserId>,
+    /// Flexible responders, who are counted on every date but aren't in `users`
+    flexible: HashSet<UserId>,
+    blocks: Vec<(RoleId, usize)>,
+    /// Users who are only counted because the user they depend on is also available
+    conditional: HashMap<UserId, UserId>,
+}
+
+impl DateTally {
+    fn count(&self) -> usize {
+        self.users.len()
+            + self.flexible.len()
+            + self.blocks.iter().map(|(_, size)| size).sum::<usize>()
+    }
+
+    fn is_available(&self, user: &UserId) -> bool {
+        self.users.contains(user) || self.flexible.contains(user)
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Scheduler {
-    owner: UserId,
+    owner: RwLock<UserId>,
     title: String,
     dates: Vec<NaiveDate>,
+    /// Dates excluded from the poll, with who excluded them if known. Dates are added
+    /// automatically when `blackout_past` is set, and schedulers saved before this was recorded
+    /// only have a list of dates.
+    #[serde(default, deserialize_with = "deserialize_blackout_dates")]
+    blackout_dates: RwLock<HashMap<NaiveDate, Option<UserId>>>,
+    /// Latest reason given for the blackout dates, shown on the scheduler message
+    #[serde(default)]
+    blackout_reason: RwLock<Option<String>>,
+    /// Asks the owner for a reason before they edit the blackout dates
+    #[serde(default)]
+    require_blackout_reason: AtomicBool,
+    /// Number of dates to suggest in the details that together reach the most responders, or 0
+    /// to not suggest any
+    #[serde(default)]
+    coverage_dates: AtomicUsize,
+    /// Number of distinct available responders the owner is aiming for, shown as progress on the
+    /// scheduler message. Unlike `max_responders`, it doesn't limit responses.
+    #[serde(default)]
+    target: RwLock<Option<usize>>,
+    /// Leaves out the field showing the date(s) with the most responses
+    #[serde(default)]
+    hide_leading: AtomicBool,
+    /// Leaves out the month headings in the results of a poll that spans several months
+    #[serde(default)]
+    hide_month_headers: AtomicBool,
+    /// Dates responders are confirming in the second round, or `None` while tentative
+    /// availability is still being collected
+    #[serde(default)]
+    shortlist: RwLock<Option<Vec<NaiveDate>>>,
+    /// Lets responders give the times they are available on each of their dates
+    #[serde(default)]
+    time_ranges: AtomicBool,
+    /// Adds dates to the blackout dates once they have passed
     #[serde(default)]
-    blackout_dates: RwLock<HashSet<NaiveDate>>,
+    blackout_past: AtomicBool,
+    /// Responders who don't take up one of the `max_responders` spots, such as event staff
+    #[serde(default)]
+    organizers: RwLock<HashSet<UserId>>,
+    /// Starts new responses with every date selected
+    #[serde(default)]
+    preselect_all: AtomicBool,
+    #[serde(default)]
+    tie_break: RwLock<TieBreak>,
+    #[serde(default)]
+    role_loss: RwLock<RoleLossPolicy>,
+    /// Responders who lost the group role and are waiting to be checked again
+    #[serde(skip)]
+    role_checks: RwLock<HashSet<UserId>>,
     group: Option<RoleId>,
-    message: MessageShim,
+    /// Replaced when the scheduler is recovered after its message is lost
+    message: RwLock<MessageShim>,
     #[serde(default)]
     repost_message: RwLock<Option<MessageShim>>,
     responses: RwLock<HashMap<UserId, Response>>,
     closed: bool,
+    #[serde(default)]
+    quorum: Option<usize>,
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    show_blackout: bool,
+    #[serde(default)]
+    max_responders: Option<usize>,
+    #[serde(default)]
+    role_blocks: RwLock<HashMap<RoleId, RoleBlock>>,
+    #[serde(default)]
+    weekday_filters: RwLock<HashMap<UserId, HashSet<Weekday>>>,
+    #[serde(default)]
+    reaction_nudge: bool,
+    /// Locked schedulers don't accept responses, but unlike closed ones can be unlocked again
+    #[serde(default)]
+    locked: AtomicBool,
+    /// Only users who have already responded can change their response
+    #[serde(default)]
+    closed_to_new: AtomicBool,
+    /// Users who have already been nudged about reacting instead of responding
+    #[serde(default)]
+    nudged: RwLock<HashSet<UserId>>,
+    /// Discussion threads created for individual dates
+    #[serde(default)]
+    threads: RwLock<HashMap<NaiveDate, ChannelId>>,
+    /// Last known names of the channels the scheduler messages are in
+    #[serde(default)]
+    channel_names: RwLock<HashMap<ChannelId, String>>,
+    #[serde(default)]
+    highlight: HighlightStyle,
+    /// Marks the earliest date that meets the quorum in the results
+    #[serde(default)]
+    mark_soonest: bool,
+    /// Remove responses from users when they leave the server
+    #[serde(default)]
+    auto_prune: bool,
+    /// Owner-provided details shown above the results
+    #[serde(default)]
+    notes: RwLock<Option<String>>,
+    /// Replaced by `visibility`; only read from schedulers saved before it existed
+    #[serde(default)]
+    hide_results: bool,
+    #[serde(default)]
+    event: RwLock<Option<EventLink>>,
+    /// Draws a bar next to each date's count in the results, scaled to the highest count
+    #[serde(default)]
+    bars: bool,
+    /// Shows the detailed results, including who responded on each date, on the scheduler
+    /// message instead of only in "Show details"
+    #[serde(default)]
+    public_details: AtomicBool,
+    /// Replaced by `visibility`; only read from schedulers saved before it existed
+    #[serde(default)]
+    open_details: bool,
+    /// What the owner, responders and everyone else can see of the results, or `None` to use
+    /// `hide_results` and `open_details`
+    #[serde(default)]
+    visibility: RwLock<Option<ResultsVisibility>>,
+    /// Adds the average availability on each weekday to the detailed results
+    #[serde(default)]
+    weekday_averages: bool,
+    /// Whether the bot pinned the scheduler message, so that it never unpins a message that
+    /// someone else pinned
+    #[serde(default)]
+    pinned: AtomicBool,
+    /// Number of messages posted below the scheduler before it is bumped automatically
+    #[serde(default)]
+    auto_bump: Option<usize>,
+    /// Minutes to wait between automatic bumps
+    #[serde(default)]
+    auto_bump_cooldown: Option<u64>,
+    #[serde(default)]
+    auto_bumps: AtomicUsize,
+    /// Messages posted in the channel since the most recent copy of the scheduler message
+    #[serde(skip)]
+    messages_since_post: AtomicUsize,
+    /// Responses aren't accepted before this time; cleared once it has passed
+    #[serde(default)]
+    opens_at: RwLock<Option<DateTime<Utc>>>,
+    /// Consecutive failed attempts to update the messages
+    #[serde(skip)]
+    edit_failures: AtomicUsize,
+    #[serde(skip)]
+    last_edit: RwLock<Option<Instant>>,
+    /// An update is waiting for the edit interval to pass, and will show any later changes too
+    #[serde(skip)]
+    edit_pending: AtomicBool,
+    #[serde(skip)]
+    last_bump: RwLock<Option<Instant>>,
+    /// When responses were last locked, if that happened since startup
+    #[serde(skip)]
+    locked_at: RwLock<Option<Instant>>,
+    /// Token of each user's most recently opened editing session. Older sessions are superseded
+    /// and can no longer submit.
+    #[serde(skip)]
+    editing_sessions: RwLock<HashMap<UserId, u64>>,
+    /// When each user last submitted a response
+    #[serde(skip)]
+    last_submits: RwLock<HashMap<UserId, Instant>>,
+    /// Owner's username, looked up the first time the messages are updated
+    #[serde(skip)]
+    owner_name: RwLock<Option<String>>,
+    /// Set once the scheduler is destroyed, so that tasks still holding it don't save it again
+    #[serde(skip)]
+    destroyed: AtomicBool,
+    /// Discord locale of the server, used to translate the scheduler's text
+    #[serde(default)]
+    locale: Option<String>,
 }
 
-impl Scheduler {
-    pub fn new(
-        owner: UserId,
-        group: Option<RoleId>,
-        message: Message,
-        limit: i64,
-        skip: Option<i64>,
-        title: &str,
-        days: HashSet<Weekday>,
-    ) -> Self {
-        let limit = limit - (limit % days.len() as i64);
-        let today = Local::now().date_naive();
-        let mut start_date = today.succ_opt().unwrap();
-        if let Some(skip) = skip {
-            start_date += Duration::weeks(skip);
-        }
-        let dates = DateRule::daily(start_date)
-            .filter(|day| days.contains(&day.weekday()))
-            .take(limit as usize)
-            .collect();
+/// Options for a new scheduler. Everything except the owner and title has a default, matching
+/// the defaults of `/schedule create`. Saved for schedulers that are posted later.
+#[derive(Serialize, Deserialize)]
+pub struct SchedulerBuilder {
+    owner: UserId,
+    title: String,
+    group: Option<RoleId>,
+    limit: i64,
+    skip: Option<i64>,
+    start_date: Option<NaiveDate>,
+    days: HashSet<Weekday>,
+    interval_weeks: Option<i64>,
+    quorum: Option<usize>,
+    show_blackout: bool,
+    max_responders: Option<usize>,
+    reaction_nudge: bool,
+    highlight: HighlightStyle,
+    mark_soonest: bool,
+    auto_prune: bool,
+    notes: Option<String>,
+    hide_results: bool,
+    bars: bool,
+    opens_at: Option<DateTime<Utc>>,
+    auto_bump: Option<usize>,
+    auto_bump_cooldown: Option<u64>,
+    weekday_averages: bool,
+    open_details: bool,
+    locale: Option<String>,
+}
+
+impl SchedulerBuilder {
+    fn new(owner: UserId, title: &str) -> Self {
         Self {
             owner,
             title: title.to_string(),
+            group: None,
+            limit: MAX_MENU_OPTIONS as i64,
+            skip: None,
+            start_date: None,
+            days: HashSet::from([Weekday::Sat, Weekday::Sun]),
+            interval_weeks: None,
+            quorum: None,
+            show_blackout: false,
+            max_responders: None,
+            reaction_nudge: false,
+            highlight: Default::default(),
+            mark_soonest: false,
+            auto_prune: false,
+            notes: None,
+            hide_results: false,
+            bars: false,
+            opens_at: None,
+            auto_bump: None,
+            auto_bump_cooldown: None,
+            weekday_averages: false,
+            open_details: false,
+            locale: None,
+        }
+    }
+
+    pub fn get_owner(&self) -> UserId {
+        self.owner
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn group(mut self, group: Option<RoleId>) -> Self {
+        self.group = group;
+        self
+    }
+
+    /// Number of dates to include, rounded down to a multiple of the number of weekdays
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Weeks to skip before the first date; ignored if a start date is given
+    pub fn skip(mut self, skip: Option<i64>) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    pub fn start_date(mut self, start_date: Option<NaiveDate>) -> Self {
+        self.start_date = start_date;
+        self
+    }
+
+    pub fn weekdays(mut self, days: HashSet<Weekday>) -> Self {
+        self.days = days;
+        self
+    }
+
+    pub fn interval_weeks(mut self, interval_weeks: Option<i64>) -> Self {
+        self.interval_weeks = interval_weeks;
+        self
+    }
+
+    pub fn quorum(mut self, quorum: Option<usize>) -> Self {
+        self.quorum = quorum;
+        self
+    }
+
+    pub fn show_blackout(mut self, show_blackout: bool) -> Self {
+        self.show_blackout = show_blackout;
+        self
+    }
+
+    pub fn max_responders(mut self, max_responders: Option<usize>) -> Self {
+        self.max_responders = max_responders;
+        self
+    }
+
+    pub fn reaction_nudge(mut self, reaction_nudge: bool) -> Self {
+        self.reaction_nudge = reaction_nudge;
+        self
+    }
+
+    pub fn highlight(mut self, highlight: HighlightStyle) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    pub fn mark_soonest(mut self, mark_soonest: bool) -> Self {
+        self.mark_soonest = mark_soonest;
+        self
+    }
+
+    pub fn auto_prune(mut self, auto_prune: bool) -> Self {
+        self.auto_prune = auto_prune;
+        self
+    }
+
+    pub fn notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    pub fn hide_results(mut self, hide_results: bool) -> Self {
+        self.hide_results = hide_results;
+        self
+    }
+
+    pub fn bars(mut self, bars: bool) -> Self {
+        self.bars = bars;
+        self
+    }
+
+    pub fn opens_at(mut self, opens_at: Option<DateTime<Utc>>) -> Self {
+        self.opens_at = opens_at;
+        self
+    }
+
+    pub fn auto_bump(mut self, auto_bump: Option<usize>, cooldown: Option<u64>) -> Self {
+        self.auto_bump = auto_bump;
+        self.auto_bump_cooldown = cooldown;
+        self
+    }
+
+    pub fn weekday_averages(mut self, weekday_averages: bool) -> Self {
+        self.weekday_averages = weekday_averages;
+        self
+    }
+
+    pub fn open_details(mut self, open_details: bool) -> Self {
+        self.open_details = open_details;
+        self
+    }
+
+    pub fn locale(mut self, locale: Option<String>) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Checks the options and returns the dates of a scheduler created with them on `today`
+    pub fn dates(&self, today: NaiveDate) -> Result<Vec<NaiveDate>, SchedulerError> {
+        if self.title.len() > MAX_TITLE {
+            return Err(SchedulerError::InvalidOptions(
+                "Description is too long".to_owned(),
+            ));
+        }
+        if self.quorum == Some(0) {
+            return Err(SchedulerError::InvalidOptions(
+                "Quorum must be at least 1".to_owned(),
+            ));
+        }
+        if self.max_responders == Some(0) {
+            return Err(SchedulerError::InvalidOptions(
+                "Max responders must be at least 1".to_owned(),
+            ));
+        }
+        Scheduler::generate_dates(
+            self.limit,
+            self.skip,
+            self.start_date,
+            &self.days,
+            self.interval_weeks,
+            today,
+        )
+    }
+
+    /// Checks the options and creates the scheduler for `message`, without saving it
+    pub fn build(self, message: Message) -> Result<Scheduler, SchedulerError> {
+        let dates = self.dates(Local::now().date_naive())?;
+        Ok(Scheduler {
+            owner: self.owner.into(),
+            title: self.title,
             dates,
             blackout_dates: Default::default(),
-            group,
-            message: message.into(),
+            blackout_reason: Default::default(),
+            require_blackout_reason: Default::default(),
+            coverage_dates: Default::default(),
+            target: Default::default(),
+            hide_leading: Default::default(),
+            hide_month_headers: Default::default(),
+            shortlist: Default::default(),
+            time_ranges: Default::default(),
+            blackout_past: Default::default(),
+            organizers: Default::default(),
+            preselect_all: Default::default(),
+            tie_break: Default::default(),
+            role_loss: Default::default(),
+            role_checks: Default::default(),
+            group: self.group,
+            message: RwLock::new(message.into()),
             repost_message: None.into(),
             responses: Default::default(),
             closed: false,
+            quorum: self.quorum,
+            created_at: Some(Utc::now()),
+            show_blackout: self.show_blackout,
+            max_responders: self.max_responders,
+            role_blocks: Default::default(),
+            weekday_filters: Default::default(),
+            reaction_nudge: self.reaction_nudge,
+            locked: Default::default(),
+            closed_to_new: Default::default(),
+            nudged: Default::default(),
+            threads: Default::default(),
+            channel_names: Default::default(),
+            highlight: self.highlight,
+            mark_soonest: self.mark_soonest,
+            auto_prune: self.auto_prune,
+            notes: self.notes.into(),
+            hide_results: false,
+            event: Default::default(),
+            bars: self.bars,
+            public_details: Default::default(),
+            open_details: false,
+            visibility: RwLock::new(Some(ResultsVisibility::from_flags(
+                self.hide_results,
+                self.open_details,
+            ))),
+            weekday_averages: self.weekday_averages,
+            pinned: Default::default(),
+            auto_bump: self.auto_bump,
+            auto_bump_cooldown: self.auto_bump_cooldown,
+            auto_bumps: Default::default(),
+            messages_since_post: Default::default(),
+            opens_at: self.opens_at.into(),
+            edit_failures: Default::default(),
+            last_edit: Default::default(),
+            edit_pending: Default::default(),
+            last_bump: Default::default(),
+            locked_at: Default::default(),
+            editing_sessions: Default::default(),
+            last_submits: Default::default(),
+            owner_name: Default::default(),
+            destroyed: Default::default(),
+            locale: self.locale,
+        })
+    }
+}
+
+impl Scheduler {
+    pub fn builder(owner: UserId, title: &str) -> SchedulerBuilder {
+        SchedulerBuilder::new(owner, title)
+    }
+
+    /// Dates a scheduler created with these options includes, starting from the day after `today`
+    /// unless a start date is given. `limit` is rounded down to a multiple of the number of
+    /// weekdays, so that each weekday is included equally often.
+    pub fn generate_dates(
+        limit: i64,
+        skip: Option<i64>,
+        start_date: Option<NaiveDate>,
+        days: &HashSet<Weekday>,
+        interval_weeks: Option<i64>,
+        today: NaiveDate,
+    ) -> Result<Vec<NaiveDate>, SchedulerError> {
+        if days.is_empty() {
+            return Err(SchedulerError::InvalidOptions(
+                "At least one weekday must be included".to_owned(),
+            ));
+        }
+        let interval = interval_weeks.unwrap_or(1);
+        if !(1..=MAX_INTERVAL_WEEKS).contains(&interval) {
+            return Err(SchedulerError::InvalidOptions(format!(
+                "Interval must be between 1 and {} weeks",
+                MAX_INTERVAL_WEEKS
+            )));
         }
+        let limit = limit - (limit % days.len() as i64);
+        if limit <= 0 {
+            return Err(SchedulerError::InvalidOptions(
+                "Limit must be at least the number of weekdays included".to_owned(),
+            ));
+        }
+        let start_date = match start_date {
+            Some(date) => date,
+            None => {
+                let skip = skip.unwrap_or(0);
+                if !(0..=MAX_SKIP_WEEKS).contains(&skip) {
+                    return Err(SchedulerError::InvalidOptions(format!(
+                        "Skip must be between 0 and {} weeks",
+                        MAX_SKIP_WEEKS
+                    )));
+                }
+                today
+                    .succ_opt()
+                    .and_then(|d| d.checked_add_signed(Duration::weeks(skip)))
+                    .ok_or_else(|| {
+                        SchedulerError::InvalidOptions("Start date is out of range".to_owned())
+                    })?
+            }
+        };
+        // Every included week has at least one included weekday, so this bounds the last date
+        if start_date
+            .checked_add_signed(Duration::weeks(limit * interval))
+            .is_none()
+        {
+            return Err(SchedulerError::InvalidOptions(
+                "Start date is out of range".to_owned(),
+            ));
+        }
+        // Each weekday first occurs in the week starting on the start date, so taking every Nth
+        // week from there takes every Nth occurrence of each weekday
+        let mut dates: Vec<NaiveDate> = DateRule::daily(start_date)
+            .filter(|day| days.contains(&day.weekday()))
+            .filter(|day| (*day - start_date).num_weeks() % interval == 0)
+            .take(limit as usize)
+            .collect();
+        // The generator should already produce each date once, in order, but a duplicated date
+        // would break the menu indices, so check rather than rely on it
+        let ordered = dates.windows(2).all(|w| w[0] < w[1]);
+        if !ordered || dates.iter().any(|d| !days.contains(&d.weekday())) {
+            error!("correcting generated dates: {:?}", dates);
+            dates.retain(|d| days.contains(&d.weekday()));
+            dates.sort();
+            dates.dedup();
+        }
+        if dates.len() != limit as usize {
+            return Err(SchedulerError::InvalidOptions(
+                "Start date is out of range".to_owned(),
+            ));
+        }
+        Ok(dates)
     }
 
     pub fn get_id(&self) -> MessageId {
-        self.message.message_id
+        self.get_message().message_id
+    }
+
+    fn get_message(&self) -> MessageShim {
+        *self.message.read().unwrap()
+    }
+
+    pub fn get_owner(&self) -> UserId {
+        *self.owner.read().unwrap()
+    }
+
+    /// Hands the scheduler over to another user, e.g. when its owner leaves the server
+    pub fn set_owner(&self, owner: UserId) -> Result<(), SchedulerError> {
+        *self.owner.write().unwrap() = owner;
+        *self.owner_name.write().unwrap() = None;
+        self.try_save()
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
     }
 
     pub fn get_repost(&self) -> Option<MessageId> {
         self.repost_message.read().unwrap().map(|m| m.message_id)
     }
 
-    fn save(&self) {
-        crate::write_file(&self.message.message_id, self);
+    /// Schedulers saved before the creation time was tracked fall back to the message timestamp
+    fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+            .unwrap_or_else(|| *self.get_id().created_at())
     }
 
-    pub async fn add_response(&self, ctx: &Context, user: UserId, response: Response) {
-        self.responses.write().unwrap().insert(user, response);
-        self.save();
-        self.update_messages(ctx).await;
+    /// Human-readable name of a channel, falling back to the id if it was never resolved
+    pub fn get_channel_name(&self, channel_id: ChannelId) -> String {
+        match self.channel_names.read().unwrap().get(&channel_id) {
+            Some(name) => format!("#{}", name),
+            None => channel_id.to_string(),
+        }
     }
 
-    pub async fn delete_response(&self, ctx: &Context, user: UserId) {
-        self.responses.write().unwrap().remove(&user);
-        self.save();
-        self.update_messages(ctx).await;
+    /// Looks up the names of the channels the scheduler messages are in. Channels that can't be
+    /// found (e.g. deleted) keep their last known name.
+    pub async fn refresh_channel_names(&self, ctx: &Context) {
+        let mut channels = vec![self.get_message().channel_id];
+        if let Some(repost) = *self.repost_message.read().unwrap() {
+            channels.push(repost.channel_id);
+        }
+        let mut changed = false;
+        for channel_id in channels.into_iter().unique() {
+            match channel_id.to_channel(ctx).await {
+                Ok(Channel::Guild(channel)) => {
+                    changed |= self.rename_channel(channel_id, &channel.name)
+                }
+                Ok(_) => (),
+                Err(e) => error!("Cannot get channel {}: {}", channel_id, e),
+            }
+        }
+        if changed {
+            self.save();
+        }
     }
 
-    pub async fn set_blackout(&self, ctx: &Context, response: Response) {
-        *self.blackout_dates.write().unwrap() = response.dates;
-        self.save();
-        self.update_messages(ctx).await;
+    /// Records a new name for a channel the scheduler is in, returning whether it changed
+    fn rename_channel(&self, channel_id: ChannelId, name: &str) -> bool {
+        let mut channel_names = self.channel_names.write().unwrap();
+        if channel_names.get(&channel_id).map(String::as_str) == Some(name) {
+            return false;
+        }
+        channel_names.insert(channel_id, name.to_owned());
+        true
     }
 
-    fn get_responses(&self) -> String {
-        let responses = self.responses.read().unwrap();
-        if responses.is_empty() {
-            "**0**".to_owned()
+    /// Handles a channel being renamed
+    pub fn channel_renamed(&self, channel_id: ChannelId, name: &str) {
+        let in_channel = self.get_message().channel_id == channel_id
+            || self.get_repost_channel() == Some(channel_id);
+        if in_channel && self.rename_channel(channel_id, name) {
+            self.save();
+        }
+    }
+
+    /// Registers a new editing session for `user`, superseding any that is already open, and
+    /// returns its token
+    fn start_editing(&self, user: UserId) -> u64 {
+        let token = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+        self.editing_sessions.write().unwrap().insert(user, token);
+        token
+    }
+
+    /// Ends an editing session, returning whether it was still the user's most recent one
+    fn finish_editing(&self, user: UserId, token: u64) -> bool {
+        let mut sessions = self.editing_sessions.write().unwrap();
+        if sessions.get(&user) == Some(&token) {
+            sessions.remove(&user);
+            true
         } else {
-            format!(
-                "**{}** ({})",
-                responses.len(),
-                responses
-                    .iter()
-                    .map(|(id, _response)| format!("<@{}>", id))
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            )
+            false
         }
     }
 
-    fn get_results(&self, detailed: bool) -> impl Iterator<Item = String> + '_ {
-        let responses = self.responses.read().unwrap();
-        let blackout_dates = self.blackout_dates.read().unwrap();
-        let results: Vec<_> = self
-            .dates
+    fn text(&self, text: Text) -> &'static str {
+        text.get(self.locale.as_deref())
+    }
+
+    fn error_text(&self, e: &SchedulerError) -> String {
+        e.localized(self.locale.as_deref())
+    }
+
+    fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    fn is_closed_to_new(&self) -> bool {
+        self.closed_to_new.load(Ordering::Relaxed)
+    }
+
+    /// Time responses open at, if that is still in the future
+    fn get_opens_at(&self) -> Option<DateTime<Utc>> {
+        self.opens_at.read().unwrap().filter(|t| *t > Utc::now())
+    }
+
+    /// Updates the messages once responses open. Schedulers that opened while the bot wasn't
+    /// running are updated straight away.
+    pub fn schedule_opening(self: Arc<Self>, ctx: &Context) {
+        let opens_at = match *self.opens_at.read().unwrap() {
+            Some(t) => t,
+            None => return,
+        };
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            if let Ok(delay) = (opens_at - Utc::now()).to_std() {
+                tokio::time::sleep(delay).await;
+            }
+            info!("responses opened: {}", self.get_id());
+            *self.opens_at.write().unwrap() = None;
+            self.save();
+            self.update_messages(&ctx).await;
+        });
+    }
+
+    fn get_repost_channel(&self) -> Option<ChannelId> {
+        self.repost_message.read().unwrap().map(|m| m.channel_id)
+    }
+
+    /// Channel of the most recent copy of the scheduler message
+    pub fn get_latest_channel(&self) -> ChannelId {
+        self.get_repost_channel()
+            .unwrap_or(self.get_message().channel_id)
+    }
+
+    /// Records a bump, or returns how much longer to wait if the last one was too recent
+    pub fn start_bump(&self) -> Result<(), std::time::Duration> {
+        self.start_bump_with_cooldown(BUMP_COOLDOWN)
+    }
+
+    fn start_bump_with_cooldown(
+        &self,
+        cooldown: std::time::Duration,
+    ) -> Result<(), std::time::Duration> {
+        let mut last_bump = self.last_bump.write().unwrap();
+        if let Some(last) = *last_bump {
+            let elapsed = last.elapsed();
+            if elapsed < cooldown {
+                return Err(cooldown - elapsed);
+            }
+        }
+        *last_bump = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Counts a message posted in the scheduler's latest channel, and returns whether the
+    /// scheduler should now be bumped automatically
+    pub fn message_posted(&self) -> bool {
+        let threshold = match self.auto_bump {
+            Some(threshold) if !self.closed => threshold,
+            _ => return false,
+        };
+        if self.auto_bumps.load(Ordering::Relaxed) >= MAX_AUTO_BUMPS {
+            return false;
+        }
+        if self.messages_since_post.fetch_add(1, Ordering::Relaxed) + 1 < threshold {
+            return false;
+        }
+        let cooldown = self.auto_bump_cooldown.unwrap_or(AUTO_BUMP_COOLDOWN);
+        if self
+            .start_bump_with_cooldown(std::time::Duration::from_secs(cooldown * 60))
+            .is_err()
+        {
+            return false;
+        }
+        self.auto_bumps.fetch_add(1, Ordering::Relaxed);
+        self.save();
+        true
+    }
+
+    /// Saves the scheduler, failing without undoing the changes in memory. Callers still update
+    /// the messages after a failure, so that they match what is in memory. Once the scheduler is
+    /// destroyed nothing is saved, since that would recreate its file.
+    fn try_save(&self) -> Result<(), SchedulerError> {
+        if self.destroyed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        crate::write_file(&self.get_id(), self)
+    }
+
+    /// Saves changes that aren't worth failing an operation over if they are lost
+    fn save(&self) {
+        if let Err(e) = self.try_save() {
+            error!("{}", e);
+        }
+    }
+
+    fn is_full(&self, responses: &HashMap<UserId, Response>, user: UserId) -> bool {
+        match self.max_responders {
+            Some(max) => {
+                !responses.contains_key(&user)
+                    && !self.organizers.read().unwrap().contains(&user)
+                    && self.get_attendee_count(responses) >= max
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_responded(&self, user: UserId) -> bool {
+        self.responses.read().unwrap().contains_key(&user)
+    }
+
+    /// Dates of a user's saved response, in order
+    #[cfg(feature = "dev-tools")]
+    pub fn get_response_dates(&self, user: UserId) -> Option<Vec<NaiveDate>> {
+        let responses = self.responses.read().unwrap();
+        let response = responses.get(&user)?;
+        Some(response.dates.iter().cloned().sorted().collect())
+    }
+
+    /// Editor actions that select some of the available dates, select all of them, clear them,
+    /// then select the same dates again and submit. `seed` says whether to select each of the
+    /// available dates. Returns the actions along with the dates the response should end up
+    /// with.
+    #[cfg(feature = "dev-tools")]
+    pub fn get_dev_script(&self, seed: &[u8]) -> (Vec<EditorAction>, Vec<NaiveDate>) {
+        let available = self.get_available_dates();
+        let (indices, dates): (Vec<String>, Vec<NaiveDate>) = self
+            .dates
             .iter()
-            .filter_map(|date| {
-                if blackout_dates.contains(date) {
-                    None
-                } else {
-                    let mut users = HashSet::new();
-                    for (user_id, response) in responses.iter() {
-                        if response.dates.contains(date) {
-                            users.insert(user_id);
+            .enumerate()
+            .filter(|(_, date)| available.contains(date))
+            .zip(seed.iter().chain(std::iter::repeat(&0)))
+            .filter(|(_, b)| *b & 1 == 1)
+            .map(|((i, date), _)| (i.to_string(), *date))
+            .unzip();
+        let action = |custom_id: &str, values: Vec<String>| EditorAction {
+            custom_id: custom_id.to_owned(),
+            values,
+        };
+        let mut actions = vec![
+            action("select", indices.clone()),
+            action("select_all", vec![]),
+        ];
+        if available.len() > SELECT_ALL_CONFIRM {
+            actions.push(action("confirm_select_all", vec![]));
+        }
+        actions.push(action("clear_all", vec![]));
+        actions.push(action("select", indices));
+        actions.push(action("submit", vec![]));
+        (actions, dates)
+    }
+
+    /// Number of responders who count toward `max_responders`, which excludes organizers
+    fn get_attendee_count(&self, responses: &HashMap<UserId, Response>) -> usize {
+        let organizers = self.organizers.read().unwrap();
+        responses
+            .keys()
+            .filter(|user| !organizers.contains(user))
+            .count()
+    }
+
+    pub fn set_tie_break(&self, user: UserId, tie_break: TieBreak) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        *self.tie_break.write().unwrap() = tie_break;
+        self.try_save()
+    }
+
+    pub fn set_preselect_all(&self, user: UserId, enabled: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        self.preselect_all.store(enabled, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    pub fn set_organizer(
+        &self,
+        user: UserId,
+        organizer: UserId,
+        is_organizer: bool,
+    ) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        let mut organizers = self.organizers.write().unwrap();
+        if is_organizer {
+            organizers.insert(organizer);
+        } else {
+            organizers.remove(&organizer);
+        }
+        drop(organizers);
+        self.try_save()
+    }
+
+    /// Notes that `user` is submitting a response, unless they already did within the submit
+    /// cooldown, in which case the submission should be rejected
+    fn record_submit(&self, user: UserId) -> bool {
+        let cooldown = SUBMIT_COOLDOWN.get().copied().unwrap_or_default();
+        let mut last_submits = self.last_submits.write().unwrap();
+        let now = Instant::now();
+        // Users whose cooldown has passed don't need to be remembered
+        last_submits.retain(|_, last| now.duration_since(*last) < cooldown);
+        if last_submits.contains_key(&user) {
+            return false;
+        }
+        last_submits.insert(user, now);
+        true
+    }
+
+    /// Checks whether `user` can submit a response from the editing session `token`, which began
+    /// at `started`. The submission time to record is returned, which is kept from the recorded
+    /// response if this is a retry of a submission from the same session that couldn't be saved;
+    /// that submission was already accepted, so it isn't checked again.
+    fn check_submission(
+        &self,
+        responses: &HashMap<UserId, Response>,
+        user: UserId,
+        started: Instant,
+        token: u64,
+    ) -> Result<Option<DateTime<Utc>>, SchedulerError> {
+        if let Some(recorded) = responses.get(&user).filter(|r| r.submission == Some(token)) {
+            return Ok(recorded.submitted_at);
+        }
+        self.check_open(started)?;
+        if self.is_closed_to_new() && !responses.contains_key(&user) {
+            return Err(SchedulerError::ClosedToNew);
+        }
+        if self.is_full(responses, user) {
+            return Err(SchedulerError::Full);
+        }
+        Ok(Some(Utc::now()))
+    }
+
+    /// Fails without recording the response if responses are locked, or if the scheduler is
+    /// full or closed to new responders and `user` is not already a responder. `started` is when
+    /// the user began editing the response in the session `token`.
+    pub async fn add_response(
+        &self,
+        host: &impl EditorHost,
+        user: UserId,
+        mut response: Response,
+        started: Instant,
+        token: u64,
+    ) -> Result<(), SchedulerError> {
+        {
+            let mut responses = self.responses.write().unwrap();
+            response.submitted_at = self.check_submission(&responses, user, started, token)?;
+            response.submission = Some(token);
+            responses.insert(user, response);
+        }
+        let saved = self.try_save();
+        host.update_messages(self).await;
+        saved
+    }
+
+    /// Records which of the shortlisted dates `user` can make, keeping their tentative response.
+    /// Fails under the same conditions as `add_response`.
+    pub async fn set_confirmed(
+        &self,
+        host: &impl EditorHost,
+        user: UserId,
+        dates: HashSet<NaiveDate>,
+        started: Instant,
+        token: u64,
+    ) -> Result<(), SchedulerError> {
+        {
+            let mut responses = self.responses.write().unwrap();
+            let submitted_at = self.check_submission(&responses, user, started, token)?;
+            let response = responses.entry(user).or_default();
+            response.confirmed = dates;
+            response.submitted_at = submitted_at;
+            response.submission = Some(token);
+        }
+        let saved = self.try_save();
+        host.update_messages(self).await;
+        saved
+    }
+
+    pub async fn delete_response(
+        &self,
+        host: &impl EditorHost,
+        user: UserId,
+    ) -> Result<(), SchedulerError> {
+        self.responses.write().unwrap().remove(&user);
+        let saved = self.try_save();
+        host.update_messages(self).await;
+        saved
+    }
+
+    pub fn set_role_loss(
+        &self,
+        user: UserId,
+        policy: RoleLossPolicy,
+    ) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        *self.role_loss.write().unwrap() = policy;
+        self.try_save()
+    }
+
+    /// Applies the role loss policy if `member` responded but no longer has the group role, and
+    /// still doesn't after `ROLE_LOSS_DELAY`
+    pub fn member_updated(
+        self: Arc<Self>,
+        ctx: &Context,
+        notifier: Arc<Notifier>,
+        member: &Member,
+    ) {
+        if self.closed {
+            return;
+        }
+        let policy = *self.role_loss.read().unwrap();
+        let role = match self.group {
+            Some(role) if policy != RoleLossPolicy::Keep && !member.roles.contains(&role) => role,
+            _ => return,
+        };
+        let (guild, user) = (member.guild_id, member.user.id);
+        // Only one check is needed however many times the member is updated meanwhile
+        if !self.has_responded(user) || !self.role_checks.write().unwrap().insert(user) {
+            return;
+        }
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(ROLE_LOSS_DELAY).await;
+            self.role_checks.write().unwrap().remove(&user);
+            // Members who left the server are handled by auto_prune instead
+            let lost = match guild.member(&ctx, user).await {
+                Ok(member) => !member.roles.contains(&role),
+                Err(_) => false,
+            };
+            if !lost || !self.has_responded(user) || self.destroyed.load(Ordering::Relaxed) {
+                return;
+            }
+            match policy {
+                RoleLossPolicy::Keep => (),
+                RoleLossPolicy::Remove => {
+                    info!(
+                        "removing response from {} without the group role in {}",
+                        user,
+                        self.get_id()
+                    );
+                    if let Err(e) = self.delete_response(&ctx, user).await {
+                        error!("{}", e);
+                    }
+                }
+                RoleLossPolicy::Notify => notifier.send(Notification {
+                    recipient: Recipient::User(self.get_owner()),
+                    reply_to: None,
+                    content: format!(
+                        "<@{}> no longer has the <@&{}> role, but their response to **{}** \
+                         is still counted",
+                        user, role, self.title
+                    ),
+                    mention_role: None,
+                }),
+            }
+        });
+    }
+
+    /// Checks whether a response that began editing at `started` can still be submitted. Locking
+    /// doesn't reject responses that were already being edited until `LOCK_GRACE` has passed, so
+    /// that users who opened the editor just in time aren't cut off.
+    fn check_open(&self, started: Instant) -> Result<(), SchedulerError> {
+        if self.closed {
+            return Err(SchedulerError::Closed);
+        }
+        if !self.is_locked() {
+            return Ok(());
+        }
+        match *self.locked_at.read().unwrap() {
+            Some(locked_at) if started < locked_at && locked_at.elapsed() < LOCK_GRACE => Ok(()),
+            _ => Err(SchedulerError::Locked),
+        }
+    }
+
+    /// Removes the response of a user who left the server, if enabled
+    pub async fn member_left(&self, ctx: &Context, user: UserId) {
+        if self.auto_prune && self.responses.read().unwrap().contains_key(&user) {
+            info!(
+                "removing response from departed member {} in {}",
+                user,
+                self.get_id()
+            );
+            if let Err(e) = self.delete_response(ctx, user).await {
+                error!("{}", e);
+            }
+        }
+    }
+
+    /// Checks that the scheduler has dates, no more than fit in a menu, and that they're in order
+    /// without repeats
+    pub fn check_dates(&self) -> Result<(), SchedulerError> {
+        let msg = if self.dates.is_empty() {
+            "The scheduler has no dates".to_owned()
+        } else if self.dates.len() > MAX_MENU_OPTIONS {
+            format!(
+                "The scheduler has {} dates, more than the {} allowed",
+                self.dates.len(),
+                MAX_MENU_OPTIONS
+            )
+        } else if !self.dates.windows(2).all(|w| w[0] < w[1]) {
+            "The scheduler's dates are out of order or repeated".to_owned()
+        } else {
+            return Ok(());
+        };
+        Err(SchedulerError::InvalidOptions(msg))
+    }
+
+    /// Checks a loaded scheduler for inconsistencies, returning a description of each problem
+    /// found, and repairs them if `fix` is set. Responders to a scheduler limited to a role are
+    /// only checked to still have it when `http` is given.
+    pub async fn validate(&mut self, http: Option<&Http>, fix: bool) -> Vec<String> {
+        let mut problems = vec![];
+        if let Err(e) = self.check_dates() {
+            problems.push(e.to_string());
+            if fix {
+                // Only the order can be repaired, not missing or excess dates
+                self.dates.sort();
+                self.dates.dedup();
+            }
+        }
+        let dates: HashSet<NaiveDate> = self.dates.iter().cloned().collect();
+        let mut check_dates = |name: String, selected: &mut HashSet<NaiveDate>| {
+            let extra = selected.iter().filter(|d| !dates.contains(d)).count();
+            if extra > 0 {
+                problems.push(format!(
+                    "{} includes {} dates that aren't in the scheduler",
+                    name, extra
+                ));
+                if fix {
+                    selected.retain(|d| dates.contains(d));
+                }
+            }
+        };
+        for (user_id, response) in self.responses.get_mut().unwrap().iter_mut() {
+            check_dates(format!("response from {}", user_id), &mut response.dates);
+        }
+        for (role_id, block) in self.role_blocks.get_mut().unwrap().iter_mut() {
+            check_dates(format!("block for role {}", role_id), &mut block.dates);
+        }
+        let blackout_dates = self.blackout_dates.get_mut().unwrap();
+        let mut selected = blackout_dates.keys().cloned().collect();
+        check_dates("blackout dates".to_owned(), &mut selected);
+        blackout_dates.retain(|date, _| selected.contains(date));
+
+        if let (Some(http), Some(role)) = (http, self.group) {
+            let guild = match self.get_message().channel_id.to_channel(http).await {
+                Ok(Channel::Guild(channel)) => Some(channel.guild_id),
+                _ => None,
+            };
+            match guild {
+                Some(guild) => {
+                    let users = self
+                        .responses
+                        .get_mut()
+                        .unwrap()
+                        .keys()
+                        .cloned()
+                        .collect_vec();
+                    let mut removed = vec![];
+                    for user_id in users {
+                        match guild.member(http, user_id).await {
+                            Ok(member) if member.roles.contains(&role) => (),
+                            Ok(_) => {
+                                problems.push(format!("{} no longer has the role", user_id));
+                                removed.push(user_id);
+                            }
+                            Err(serenity::Error::Http(e))
+                                if matches!(&*e, HttpError::UnsuccessfulRequest(r)
+                                    if r.error.code == UNKNOWN_MEMBER) =>
+                            {
+                                problems.push(format!("{} is no longer in the server", user_id));
+                                removed.push(user_id);
+                            }
+                            Err(e) => problems.push(format!("cannot check {}: {}", user_id, e)),
                         }
                     }
-                    Some((date, users))
+                    if fix {
+                        let responses = self.responses.get_mut().unwrap();
+                        for user_id in removed {
+                            responses.remove(&user_id);
+                        }
+                    }
+                }
+                None => problems.push("cannot find the scheduler's server".to_owned()),
+            }
+        }
+        problems
+    }
+
+    /// Removes responses from users who are no longer members of `guild`, returning how many
+    /// were removed
+    async fn remove_departed(&self, ctx: &Context, guild: GuildId) -> usize {
+        let users = self.responses.read().unwrap().keys().cloned().collect_vec();
+        let mut departed = vec![];
+        for user_id in users {
+            match guild.member(ctx, user_id).await {
+                Ok(_) => (),
+                Err(serenity::Error::Http(e))
+                    if matches!(&*e, HttpError::UnsuccessfulRequest(r)
+                        if r.error.code == UNKNOWN_MEMBER) =>
+                {
+                    departed.push(user_id)
+                }
+                Err(e) => error!("Cannot get member {}: {}", user_id, e),
+            }
+        }
+        if !departed.is_empty() {
+            {
+                let mut responses = self.responses.write().unwrap();
+                let mut weekday_filters = self.weekday_filters.write().unwrap();
+                for user_id in departed.iter() {
+                    responses.remove(user_id);
+                    weekday_filters.remove(user_id);
+                }
+            }
+            self.save();
+            self.update_messages(ctx).await;
+        }
+        departed.len()
+    }
+
+    /// Lets the owner pick a date and creates a Discord scheduled event for it, or moves the
+    /// existing one to that date
+    pub async fn create_event(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        let mut session = ComponentSession::new(ctx, component);
+        if component.user.id != self.get_owner() {
+            session
+                .respond(
+                    Some("Only the scheduler owner may create events".to_owned()),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        let tally = self.get_tally();
+        if tally.is_empty() {
+            session
+                .respond(
+                    Some("No dates available".to_owned()),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+        let mut components = CreateComponents::default();
+        components.create_action_row(|ar| {
+            ar.create_select_menu(|menu| {
+                menu.custom_id("event_date")
+                    .placeholder("Date of the event")
+                    .options(|m| {
+                        for (i, t) in tally.iter().enumerate() {
+                            m.create_option(|opt| {
+                                opt.label(format!("{} ({})", t.date.format("%a %b %d"), t.count()))
+                                    .value(i)
+                            });
+                        }
+                        m
+                    })
+            })
+        });
+        session
+            .respond(None, components)
+            .await
+            .expect("Cannot send response");
+        let date = match session.next_action(CONFIRM_TIMEOUT).await {
+            Some(action) => match action.values.first().and_then(|v| v.parse::<usize>().ok()) {
+                Some(i) => tally[i].date,
+                None => return,
+            },
+            None => {
+                session
+                    .edit(Some("Timed out".to_owned()), CreateComponents::default())
+                    .await
+                    .map_err(|e| error!("Cannot update message: {}", e))
+                    .ok();
+                return;
+            }
+        };
+
+        let text = match self.set_event(ctx, component.guild_id, date).await {
+            Ok(()) => format!("Event set for {}", date.format("%a %b %d")),
+            Err(e) => {
+                error!("Cannot create event: {}", e);
+                format!(
+                    "Cannot create event; check that the bot has the Manage Events permission ({})",
+                    e
+                )
+            }
+        };
+        session
+            .edit(Some(text), CreateComponents::default())
+            .await
+            .map_err(|e| error!("Cannot update message: {}", e))
+            .ok();
+    }
+
+    async fn set_event(
+        &self,
+        ctx: &Context,
+        guild: Option<GuildId>,
+        date: NaiveDate,
+    ) -> serenity::Result<()> {
+        let guild_id = guild.ok_or(serenity::Error::Other("Events require a server"))?;
+        let start = date
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        // Events can't start in the past
+        let start = start.max(Utc::now() + Duration::minutes(1));
+        let end = (date + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        let name: String = self.title.chars().take(100).collect();
+        let message = self
+            .repost_message
+            .read()
+            .unwrap()
+            .unwrap_or(self.get_message());
+        let location = format!(
+            "https://discord.com/channels/{}/{}/{}",
+            guild_id, message.channel_id, message.message_id
+        );
+        let existing = *self.event.read().unwrap();
+        let edited = match existing {
+            Some(event) if event.guild_id == guild_id => guild_id
+                .edit_scheduled_event(ctx, event.event_id, |e| {
+                    e.name(&name)
+                        .location(&location)
+                        .start_time(start)
+                        .end_time(end)
+                })
+                .await
+                .map_err(|e| error!("Cannot update event, creating a new one: {}", e))
+                .ok(),
+            _ => None,
+        };
+        let event = match edited {
+            Some(event) => event,
+            None => {
+                guild_id
+                    .create_scheduled_event(ctx, |e| {
+                        e.name(&name)
+                            .kind(ScheduledEventType::External)
+                            .location(&location)
+                            .start_time(start)
+                            .end_time(end)
+                    })
+                    .await?
+            }
+        };
+        *self.event.write().unwrap() = Some(EventLink {
+            guild_id,
+            event_id: event.id,
+            date,
+        });
+        self.save();
+        self.update_messages(ctx).await;
+        Ok(())
+    }
+
+    /// Shows a dialog for the owner to edit the notes shown on the scheduler message
+    pub async fn edit_notes(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        if component.user.id != self.get_owner() {
+            component
+                .create_interaction_response(ctx, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|m| {
+                            m.content("Only the scheduler owner may edit notes")
+                                .ephemeral(true)
+                        })
+                })
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        let current = self.notes.read().unwrap().clone();
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::Modal)
+                    .interaction_response_data(|d| {
+                        d.custom_id("notes").title("Edit notes").components(|c| {
+                            c.create_action_row(|ar| {
+                                ar.create_input_text(|t| {
+                                    t.custom_id("notes")
+                                        .label("Notes")
+                                        .style(InputTextStyle::Paragraph)
+                                        .max_length(MAX_NOTES as u64)
+                                        .required(false);
+                                    if let Some(current) = current {
+                                        t.value(current);
+                                    }
+                                    t
+                                })
+                            })
+                        })
+                    })
+            })
+            .await
+            .expect("Cannot show dialog");
+        let modal = match component
+            .message
+            .await_modal_interaction(ctx)
+            .author_id(component.user.id)
+            .timeout(NOTES_TIMEOUT)
+            .await
+        {
+            Some(m) => m,
+            None => return,
+        };
+        let notes = modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .find_map(|c| match c {
+                ActionRowComponent::InputText(t) => Some(t.value.trim().to_owned()),
+                _ => None,
+            })
+            .filter(|notes| !notes.is_empty());
+        *self.notes.write().unwrap() = notes;
+        self.save();
+        modal
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content("Notes updated").ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        self.update_messages(ctx).await;
+    }
+
+    pub fn set_time_ranges(&self, user: UserId, enabled: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        self.time_ranges.store(enabled, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    /// Shows a dialog for a responder to give the times they are available on each of the dates
+    /// they selected, one line per date
+    pub async fn edit_times(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        let user = component.user.id;
+        let started = Instant::now();
+        let response = self.responses.read().unwrap().get(&user).cloned();
+        let dates = response
+            .as_ref()
+            .map(|response| {
+                self.get_available_dates()
+                    .into_iter()
+                    .filter(|d| response.is_available(d))
+                    .sorted()
+                    .collect_vec()
+            })
+            .unwrap_or_default();
+        let error = match self.check_open(started) {
+            Err(e) => Some(self.error_text(&e)),
+            Ok(()) if dates.is_empty() => {
+                Some("Add a response with the dates you're available first".to_owned())
+            }
+            Ok(()) => None,
+        };
+        if let Some(error) = error {
+            component
+                .create_interaction_response(ctx, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|m| m.content(error).ephemeral(true))
+                })
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        let times = response.map(|r| r.times).unwrap_or_default();
+        let current = dates
+            .iter()
+            .map(|date| match times.get(date) {
+                Some((start, end)) => format!(
+                    "{} {}-{}",
+                    date.format("%Y-%m-%d"),
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                ),
+                None => format!("{} ", date.format("%Y-%m-%d")),
+            })
+            .join("\n");
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::Modal)
+                    .interaction_response_data(|d| {
+                        d.custom_id("times")
+                            .title("Available times")
+                            .components(|c| {
+                                c.create_action_row(|ar| {
+                                    ar.create_input_text(|t| {
+                                        t.custom_id("times")
+                                            .label("Times, e.g. 2024-01-05 6pm-9pm")
+                                            .style(InputTextStyle::Paragraph)
+                                            .required(false)
+                                            .value(current)
+                                    })
+                                })
+                            })
+                    })
+            })
+            .await
+            .expect("Cannot show dialog");
+        let modal = match component
+            .message
+            .await_modal_interaction(ctx)
+            .author_id(user)
+            .timeout(NOTES_TIMEOUT)
+            .await
+        {
+            Some(m) => m,
+            None => return,
+        };
+        let text = modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .find_map(|c| match c {
+                ActionRowComponent::InputText(t) => Some(t.value.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let content = match self.set_times(ctx, user, &dates, &text, started).await {
+            Ok(()) => "Times updated".to_owned(),
+            Err(e) => e,
+        };
+        modal
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(content).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+    }
+
+    /// Replaces `user`'s times with those listed in `text`, which must all be for `dates`, if the
+    /// scheduler still accepts responses edited since `started`. Dates that are listed without a
+    /// time range have their times cleared.
+    async fn set_times(
+        &self,
+        ctx: &Context,
+        user: UserId,
+        dates: &[NaiveDate],
+        text: &str,
+        started: Instant,
+    ) -> Result<(), String> {
+        // The dialog may have been open long enough for the scheduler to be locked
+        self.check_open(started).map_err(|e| self.error_text(&e))?;
+        let mut times = HashMap::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (date, range) = line.split_once(' ').unwrap_or((line, ""));
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .filter(|d| dates.contains(d))
+                .ok_or_else(|| format!("Not one of your dates: {}", line))?;
+            if range.trim().is_empty() {
+                continue;
+            }
+            let range = range
+                .split_once(['-', '–'])
+                .and_then(|(start, end)| Some((parse_time(start)?, parse_time(end)?)))
+                .filter(|(start, end)| start < end)
+                .ok_or_else(|| format!("Cannot read the time range: {}", line))?;
+            times.insert(date, range);
+        }
+        {
+            let mut responses = self.responses.write().unwrap();
+            let response = responses
+                .get_mut(&user)
+                .ok_or_else(|| "Your response was deleted".to_owned())?;
+            response.times = times;
+        }
+        let saved = self.try_save();
+        // The best time windows are shown on the scheduler message
+        self.update_messages(ctx).await;
+        saved.map_err(|e| self.error_text(&e))
+    }
+
+    /// The longest time window on each date that works for the most responders who gave times, if
+    /// the scheduler asks for times
+    fn get_time_windows(&self) -> Vec<String> {
+        if !self.time_ranges.load(Ordering::Relaxed) {
+            return vec![];
+        }
+        let responses = self.responses.read().unwrap();
+        let mut lines = vec![];
+        for date in self.get_available_dates().into_iter().sorted() {
+            let ranges = responses
+                .values()
+                .filter(|r| r.is_available(&date))
+                .filter_map(|r| r.times.get(&date))
+                .collect_vec();
+            // The best window always starts when one of the ranges does, and lasts until the
+            // earliest end among the ranges that cover that start
+            let best = ranges
+                .iter()
+                .map(|(start, _)| {
+                    let covering = ranges
+                        .iter()
+                        .filter(|(s, e)| s <= start && start < e)
+                        .collect_vec();
+                    let end = covering.iter().map(|(_, e)| *e).min().unwrap();
+                    (covering.len(), end - *start, *start, end)
+                })
+                .max_by_key(|(count, length, start, _)| {
+                    (*count, *length, std::cmp::Reverse(*start))
+                });
+            if let Some((count, _, start, end)) = best {
+                lines.push(format!(
+                    "{}: {}–{} works for {} {}",
+                    date.format("%a %b %d"),
+                    start.format("%-I:%M%P"),
+                    end.format("%-I:%M%P"),
+                    count,
+                    if count == 1 { "person" } else { "people" }
+                ));
+            }
+        }
+        lines
+    }
+
+    pub fn requires_blackout_reason(&self) -> bool {
+        self.require_blackout_reason.load(Ordering::Relaxed)
+    }
+
+    pub fn set_require_blackout_reason(
+        &self,
+        user: UserId,
+        required: bool,
+    ) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        self.require_blackout_reason
+            .store(required, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    /// Shows a dialog for the owner to give the reason for the blackout dates before editing
+    /// them. Returns the dialog's submission, which the blackout editor is sent in response to,
+    /// or `None` if it wasn't submitted in time.
+    pub async fn ask_blackout_reason(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+    ) -> Option<Arc<ModalSubmitInteraction>> {
+        let current = self.blackout_reason.read().unwrap().clone();
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::Modal)
+                    .interaction_response_data(|d| {
+                        d.custom_id("blackout_reason")
+                            .title("Blackout dates")
+                            .components(|c| {
+                                c.create_action_row(|ar| {
+                                    ar.create_input_text(|t| {
+                                        t.custom_id("reason")
+                                            .label("Reason, e.g. holidays")
+                                            .style(InputTextStyle::Short)
+                                            .max_length(MAX_BLACKOUT_REASON)
+                                            .required(true);
+                                        if let Some(current) = current {
+                                            t.value(current);
+                                        }
+                                        t
+                                    })
+                                })
+                            })
+                    })
+            })
+            .await
+            .expect("Cannot show dialog");
+        let modal = component
+            .message
+            .await_modal_interaction(ctx)
+            .author_id(component.user.id)
+            .timeout(NOTES_TIMEOUT)
+            .await?;
+        let reason = modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .find_map(|c| match c {
+                ActionRowComponent::InputText(t) => Some(t.value.trim().to_owned()),
+                _ => None,
+            })
+            .filter(|reason| !reason.is_empty());
+        *self.blackout_reason.write().unwrap() = reason;
+        self.save();
+        Some(modal)
+    }
+
+    /// Channel to create discussion threads in. Threads can't contain threads, so for a
+    /// scheduler posted in a thread they go in its parent channel, which isn't possible for a
+    /// forum post.
+    async fn get_thread_channel(&self, ctx: &Context) -> Result<ChannelId, &'static str> {
+        let channel_id = self.get_message().channel_id;
+        let channel = match channel_id.to_channel(ctx).await {
+            Ok(Channel::Guild(channel)) => channel,
+            _ => return Ok(channel_id),
+        };
+        let parent = match channel.parent_id {
+            Some(parent) if is_thread(channel.kind) => {
+                let kind = match parent.to_channel(ctx).await {
+                    Ok(Channel::Guild(parent)) => Some(parent.kind),
+                    _ => None,
+                };
+                Some((parent, kind))
+            }
+            _ => None,
+        };
+        choose_thread_channel(channel_id, channel.kind, parent)
+    }
+
+    /// Owner action to remove responses from users who left the server
+    pub async fn prune(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        component.defer(ctx).await.unwrap();
+        let text = if component.user.id != self.get_owner() {
+            "Only the scheduler owner may remove responses".to_owned()
+        } else {
+            let guild = component.guild_id.expect("Cannot get guild");
+            match self.remove_departed(ctx, guild).await {
+                0 => "No responses from members who left the server".to_owned(),
+                count => format!(
+                    "Removed {} responses from members who left the server",
+                    count
+                ),
+            }
+        };
+        component
+            .create_followup_message(ctx, |m| m.ephemeral(true).content(text))
+            .await
+            .expect("Cannot send message");
+    }
+
+    /// Weekdays each responder is considered available on, based on having selected at least
+    /// half of the (non-blackout) dates falling on that weekday
+    fn get_weekday_availability(&self) -> HashMap<UserId, HashSet<Weekday>> {
+        let responses = self.responses.read().unwrap();
+        let blackout_dates = self.blackout_dates.read().unwrap();
+        let totals = self
+            .dates
+            .iter()
+            .filter(|d| !blackout_dates.contains_key(d))
+            .counts_by(|d| d.weekday());
+        responses
+            .iter()
+            .map(|(user_id, response)| {
+                let weekdays = response
+                    .dates
+                    .iter()
+                    .filter(|d| !blackout_dates.contains_key(d))
+                    .counts_by(|d| d.weekday())
+                    .into_iter()
+                    .filter(|(day, count)| count * 2 >= totals.get(day).copied().unwrap_or(0))
+                    .map(|(day, _)| day)
+                    .collect();
+                (*user_id, weekdays)
+            })
+            .collect()
+    }
+
+    /// Pre-populates responses using the weekday availability of responders to `previous`.
+    /// Responders who no longer have the group role are skipped.
+    pub async fn seed_responses(
+        &self,
+        ctx: &Context,
+        guild: Option<GuildId>,
+        previous: &Scheduler,
+    ) {
+        for (user_id, weekdays) in previous.get_weekday_availability() {
+            if let Some(role) = self.group {
+                let allowed = match guild {
+                    Some(guild) => guild
+                        .member(ctx, user_id)
+                        .await
+                        .map(|m| m.roles.contains(&role))
+                        .unwrap_or(false),
+                    None => false,
+                };
+                if !allowed {
+                    continue;
+                }
+            }
+            let dates: HashSet<NaiveDate> = self
+                .dates
+                .iter()
+                .filter(|d| weekdays.contains(&d.weekday()))
+                .cloned()
+                .collect();
+            if !dates.is_empty() {
+                self.responses
+                    .write()
+                    .unwrap()
+                    .insert(user_id, dates.into());
+            }
+        }
+        info!(
+            "seeded {} responses from {}",
+            self.responses.read().unwrap().len(),
+            previous.get_id()
+        );
+    }
+
+    /// Copies the responses and blackout dates of `source` into this scheduler, limited to the
+    /// dates they have in common. When a user responded to both, the more recently submitted
+    /// response is kept, or this scheduler's if that can't be told. New responders are added in
+    /// the order they submitted their responses until the scheduler is full.
+    pub async fn merge_from(
+        &self,
+        ctx: &Context,
+        source: &Scheduler,
+    ) -> Result<MergeReport, SchedulerError> {
+        let dates: HashSet<NaiveDate> = self.dates.iter().cloned().collect();
+        if !source.dates.iter().any(|d| dates.contains(d)) {
+            return Err(SchedulerError::InvalidOptions(
+                "The schedulers don't have any dates in common".to_owned(),
+            ));
+        }
+        let mut report = MergeReport {
+            merged: 0,
+            conflicts: vec![],
+            left_out: vec![],
+        };
+        {
+            let mut responses = self.responses.write().unwrap();
+            let source_responses = source.responses.read().unwrap();
+            for (user_id, response) in source_responses
+                .iter()
+                .sorted_by_key(|(_, r)| r.submitted_at)
+            {
+                let mut response = response.clone();
+                response.dates.retain(|d| dates.contains(d));
+                // The source's sessions can't retry submissions to this scheduler
+                response.submission = None;
+                if let Some(existing) = responses.get(user_id) {
+                    let newer = response.submitted_at > existing.submitted_at;
+                    report.conflicts.push((*user_id, newer));
+                    if !newer {
+                        continue;
+                    }
+                } else if self.is_full(&responses, *user_id) {
+                    report.left_out.push(*user_id);
+                    continue;
+                }
+                responses.insert(*user_id, response);
+                report.merged += 1;
+            }
+        }
+        {
+            let mut blackout_dates = self.blackout_dates.write().unwrap();
+            for (date, user) in source.blackout_dates.read().unwrap().iter() {
+                if dates.contains(date) {
+                    blackout_dates.entry(*date).or_insert(*user);
+                }
+            }
+        }
+        info!(
+            "merged {} responses from {} into {}",
+            report.merged,
+            source.get_id(),
+            self.get_id()
+        );
+        let saved = self.try_save();
+        self.update_messages(ctx).await;
+        saved.map(|()| report)
+    }
+
+    pub async fn set_role_block(
+        &self,
+        host: &impl EditorHost,
+        role: RoleId,
+        size: usize,
+        response: Response,
+        started: Instant,
+    ) -> Result<(), SchedulerError> {
+        self.check_open(started)?;
+        let block = RoleBlock {
+            dates: response.dates,
+            size,
+        };
+        self.role_blocks.write().unwrap().insert(role, block);
+        let saved = self.try_save();
+        host.update_messages(self).await;
+        saved
+    }
+
+    pub async fn delete_role_block(
+        &self,
+        host: &impl EditorHost,
+        role: RoleId,
+    ) -> Result<(), SchedulerError> {
+        self.role_blocks.write().unwrap().remove(&role);
+        let saved = self.try_save();
+        host.update_messages(self).await;
+        saved
+    }
+
+    /// Replaces the blackout dates, attributing any that weren't already blacked out to `user`
+    pub async fn set_blackout(
+        &self,
+        host: &impl EditorHost,
+        user: UserId,
+        response: Response,
+    ) -> Result<(), SchedulerError> {
+        {
+            let mut blackout_dates = self.blackout_dates.write().unwrap();
+            blackout_dates.retain(|date, _| response.dates.contains(date));
+            for date in response.dates {
+                blackout_dates.entry(date).or_insert(Some(user));
+            }
+        }
+        let saved = self.try_save();
+        host.update_messages(self).await;
+        saved
+    }
+
+    pub fn set_blackout_past(&self, user: UserId, enabled: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        self.blackout_past.store(enabled, Ordering::Relaxed);
+        if enabled {
+            self.add_past_blackouts(Local::now().date_naive());
+        }
+        self.try_save()
+    }
+
+    /// Blacks out the dates before `today` if enabled, so that they are left out of the results
+    /// and the date menu
+    pub async fn blackout_past_dates(&self, ctx: &Context, today: NaiveDate) {
+        if self.closed || !self.blackout_past.load(Ordering::Relaxed) {
+            return;
+        }
+        if self.add_past_blackouts(today) {
+            info!("blacking out past dates of scheduler {}", self.get_id());
+            self.save();
+            self.update_messages(ctx).await;
+        }
+    }
+
+    /// Returns whether any dates were newly blacked out
+    fn add_past_blackouts(&self, today: NaiveDate) -> bool {
+        let mut blackout_dates = self.blackout_dates.write().unwrap();
+        let count = blackout_dates.len();
+        for date in self.dates.iter().filter(|d| **d < today) {
+            blackout_dates.entry(*date).or_insert(None);
+        }
+        blackout_dates.len() > count
+    }
+
+    /// Number of responders, out of the maximum if there is one
+    fn get_response_count(&self, responses: &HashMap<UserId, Response>) -> String {
+        match self.max_responders {
+            Some(max) => {
+                let attendees = self.get_attendee_count(responses);
+                match responses.len() - attendees {
+                    0 => format!("{}/{}", attendees, max),
+                    1 => format!("{}/{} + 1 organizer", attendees, max),
+                    organizers => format!("{}/{} + {} organizers", attendees, max, organizers),
+                }
+            }
+            None => responses.len().to_string(),
+        }
+    }
+
+    /// Number of responders available on at least one date, which excludes those who declined
+    /// or removed every date
+    fn get_available_count(responses: &HashMap<UserId, Response>) -> usize {
+        responses
+            .values()
+            .filter(|r| r.flexible || !r.dates.is_empty())
+            .count()
+    }
+
+    /// Mentions of every responder, in a stable order
+    fn get_responder_mentions(&self) -> Vec<String> {
+        self.responses
+            .read()
+            .unwrap()
+            .keys()
+            .sorted()
+            .map(|id| format!("<@{}>", id))
+            .collect()
+    }
+
+    /// Text of the "Responded" field, and whether the list of responders had to be shortened to
+    /// fit in it
+    fn get_responses(&self) -> (String, bool) {
+        let responses = self.responses.read().unwrap();
+        let count = self.get_response_count(&responses);
+        if responses.is_empty() {
+            return (format!("**{}**", count), false);
+        }
+        let declined = responses.values().filter(|r| r.declined).count();
+        let mut suffix = format!("; {} available", Self::get_available_count(&responses));
+        drop(responses);
+        if declined > 0 {
+            suffix += &format!(", {} declined", declined);
+        }
+        // Leave room for the count, the suffix, and the number of responders left out
+        let budget = FIELD_LIMIT - count.len() - suffix.len() - "**** (, +99999 more)".len();
+        let mentions = self.get_responder_mentions();
+        let mut list = String::new();
+        let mut shown = 0;
+        for mention in mentions.iter() {
+            if list.len() + mention.len() + 2 > budget {
+                break;
+            }
+            if shown > 0 {
+                list += ", ";
+            }
+            list += mention;
+            shown += 1;
+        }
+        let collapsed = shown < mentions.len();
+        if collapsed {
+            list = format!("{}, +{} more", list, mentions.len() - shown);
+        }
+        (format!("**{}** ({}){}", count, list, suffix), collapsed)
+    }
+
+    /// Users who declined all of the dates
+    fn get_declined(&self) -> Vec<UserId> {
+        self.responses
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, r)| r.declined)
+            .map(|(user_id, _)| *user_id)
+            .sorted()
+            .collect()
+    }
+
+    /// Reasons given by users who declined all of the dates
+    fn get_decline_reasons(&self) -> Vec<String> {
+        self.responses
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, r)| r.declined)
+            .filter_map(|(user_id, r)| Some((*user_id, r.decline_reason.as_ref()?)))
+            .sorted_by_key(|(user_id, _)| *user_id)
+            .map(|(user_id, reason)| {
+                format!("<@{}> declined: {}", user_id, escape_markdown(reason))
+            })
+            .collect()
+    }
+
+    /// Availability for each non-blackout date, in date order. Built in a single pass over the
+    /// responses, since checking every response for every date is slow for large polls.
+    fn get_tally(&self) -> Vec<DateTally> {
+        let responses = self.responses.read().unwrap();
+        let blackout_dates = self.blackout_dates.read().unwrap();
+        let role_blocks = self.role_blocks.read().unwrap();
+        let mut results = self
+            .dates
+            .iter()
+            .filter(|date| !blackout_dates.contains_key(date))
+            .map(|date| DateTally {
+                date: *date,
+                users: HashSet::new(),
+                flexible: HashSet::new(),
+                blocks: vec![],
+                conditional: HashMap::new(),
+            })
+            .collect_vec();
+        let index: HashMap<NaiveDate, usize> = results
+            .iter()
+            .enumerate()
+            .map(|(i, tally)| (tally.date, i))
+            .collect();
+        for (user_id, response) in responses.iter() {
+            // Only the dependency's own selection is checked, not whether they are counted
+            // themselves, so cycles of conditions don't exclude everyone in them
+            let dependency = response.depends_on.map(|d| (d, responses.get(&d)));
+            let satisfied = |date: &NaiveDate| match dependency {
+                Some((_, Some(dependency))) => dependency.is_available(date),
+                Some((_, None)) => false,
+                None => true,
+            };
+            if response.flexible {
+                for tally in results.iter_mut().filter(|t| satisfied(&t.date)) {
+                    tally.flexible.insert(*user_id);
+                }
+                continue;
+            }
+            for date in response.dates.iter().filter(|d| satisfied(d)) {
+                if let Some(&i) = index.get(date) {
+                    results[i].users.insert(*user_id);
+                    if let Some((dependency, _)) = dependency {
+                        results[i].conditional.insert(*user_id, dependency);
+                    }
+                }
+            }
+        }
+        for (role_id, block) in role_blocks.iter() {
+            for date in block.dates.iter() {
+                if let Some(&i) = index.get(date) {
+                    results[i].blocks.push((*role_id, block.size));
+                }
+            }
+        }
+        for tally in results.iter_mut() {
+            tally.blocks.sort();
+        }
+        results
+    }
+
+    /// Average number of responses on each weekday, excluding blackout dates, if enabled and the
+    /// scheduler has more than one weekday
+    fn get_weekday_averages(&self) -> Option<String> {
+        if !self.weekday_averages {
+            return None;
+        }
+        let blackout_dates = self.blackout_dates.read().unwrap().clone();
+        let groups = self
+            .get_tally()
+            .into_iter()
+            .filter(|t| !blackout_dates.contains_key(&t.date))
+            .sorted_by_key(|t| t.date.weekday().num_days_from_monday())
+            .group_by(|t| t.date.weekday())
+            .into_iter()
+            .map(|(_, group)| group.collect_vec())
+            .collect_vec();
+        if groups.len() < 2 {
+            return None;
+        }
+        let averages = groups
+            .iter()
+            .map(|group| {
+                let total: usize = group.iter().map(|t| t.count()).sum();
+                format!(
+                    "{}s {:.1}",
+                    group[0].date.format("%A"),
+                    total as f64 / group.len() as f64
+                )
+            })
+            .join(", ");
+        Some(format!("Average by weekday: {}", averages))
+    }
+
+    /// The combination of `coverage_dates` dates that together reach the most distinct
+    /// responders, for events with several sessions, if enabled. Role blocks aren't counted since
+    /// they aren't distinct people.
+    fn get_coverage(&self) -> Option<String> {
+        let k = self.coverage_dates.load(Ordering::Relaxed);
+        if k == 0 {
+            return None;
+        }
+        let tallies = self
+            .get_tally()
+            .into_iter()
+            .map(|t| {
+                let users: HashSet<UserId> = t.users.union(&t.flexible).cloned().collect();
+                (t.date, users)
+            })
+            .filter(|(_, users)| !users.is_empty())
+            .collect_vec();
+        if tallies.len() < k {
+            return None;
+        }
+        // Checking every combination is exact, and cheap with at most 25 dates and 4 picks
+        let mut best: Option<(Vec<NaiveDate>, usize)> = None;
+        for combination in tallies.iter().combinations(k) {
+            let reach = combination
+                .iter()
+                .flat_map(|(_, users)| users.iter())
+                .unique()
+                .count();
+            if best.as_ref().is_none_or(|(_, best)| reach > *best) {
+                best = Some((combination.iter().map(|(date, _)| *date).collect(), reach));
+            }
+        }
+        let (dates, reach) = best?;
+        Some(format!(
+            "Best {} dates for coverage: {} (reaches {} people)",
+            k,
+            dates.iter().map(|d| d.format("%a %b %d")).join(" + "),
+            reach
+        ))
+    }
+
+    pub fn set_coverage_dates(&self, user: UserId, dates: usize) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        if dates > MAX_COVERAGE_DATES {
+            return Err(SchedulerError::InvalidOptions(format!(
+                "At most {} dates can be suggested for coverage",
+                MAX_COVERAGE_DATES
+            )));
+        }
+        self.coverage_dates.store(dates, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    pub fn set_target(&self, user: UserId, target: Option<usize>) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        *self.target.write().unwrap() = target;
+        self.try_save()
+    }
+
+    pub fn set_show_leading(&self, user: UserId, show: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        self.hide_leading.store(!show, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    pub fn set_month_headers(&self, user: UserId, show: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        self.hide_month_headers.store(!show, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    fn get_shortlist(&self) -> Option<Vec<NaiveDate>> {
+        self.shortlist.read().unwrap().clone()
+    }
+
+    /// Starts the second round, in which responders confirm which of the `count` dates with the
+    /// most responses they can make, or goes back to the first round if `count` is 0. Existing
+    /// confirmations are kept, so the shortlist can be changed during the second round.
+    pub fn set_shortlist(&self, user: UserId, count: usize) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        let shortlist = if count == 0 {
+            None
+        } else {
+            let results = self.get_tally();
+            if Self::get_max_count(&results) == 0 {
+                return Err(SchedulerError::InvalidOptions(
+                    "There are no responses to shortlist dates from".to_owned(),
+                ));
+            }
+            let tie_break = *self.tie_break.read().unwrap();
+            let by_count = results
+                .iter()
+                .sorted_by_key(|t| (std::cmp::Reverse(t.count()), t.date))
+                .group_by(|t| t.count());
+            Some(
+                by_count
+                    .into_iter()
+                    .flat_map(|(_, tied)| tie_break.order(tied.map(|t| t.date).collect()))
+                    .take(count)
+                    .sorted()
+                    .collect(),
+            )
+        };
+        *self.shortlist.write().unwrap() = shortlist;
+        self.try_save()
+    }
+
+    /// Highest availability of any date, which is what the best dates are highlighted by
+    fn get_max_count(results: &[DateTally]) -> usize {
+        results.iter().map(|t| t.count()).max().unwrap_or(0)
+    }
+
+    /// Value of the embed field showing the date(s) with the most responses so far, if enabled
+    fn get_leading_field(&self) -> Option<String> {
+        if self.hide_leading.load(Ordering::Relaxed) {
+            return None;
+        }
+        let results = self.get_tally();
+        let max = Self::get_max_count(&results);
+        if max == 0 {
+            return Some(self.text(Text::NoResponsesYet).to_owned());
+        }
+        let tied = results
+            .iter()
+            .filter(|t| t.count() == max)
+            .map(|t| t.date)
+            .collect_vec();
+        let dates = match self.tie_break.read().unwrap().pick(&tied) {
+            Some(date) => vec![date],
+            None => tied,
+        };
+        let dates = dates.iter().map(|d| d.format("%a %b %-d")).join(", ");
+        Some(format!("{} ({})", dates, max))
+    }
+
+    /// Value of the embed field showing progress toward the target, if one is set
+    fn get_target_field(&self) -> Option<String> {
+        let target = (*self.target.read().unwrap())?;
+        let available = Self::get_available_count(&self.responses.read().unwrap());
+        let text = if available >= target {
+            Text::GoalReached
+        } else {
+            Text::TowardGoal
+        };
+        Some(format!("**{}/{}** {}", available, target, self.text(text)))
+    }
+
+    /// Dates that meet the quorum, or `None` if no quorum is set
+    fn get_quorum_dates(&self) -> Option<Vec<NaiveDate>> {
+        let quorum = self.quorum?;
+        Some(
+            self.get_tally()
+                .into_iter()
+                .filter(|t| t.count() >= quorum)
+                .map(|t| t.date)
+                .collect(),
+        )
+    }
+
+    /// Name and value of the embed field showing quorum status, if a quorum is set
+    fn get_quorum_field(&self) -> Option<(String, String)> {
+        self.get_quorum_dates().map(|dates| {
+            let name = format!("Quorum ({})", self.quorum.unwrap_or_default());
+            let value = if dates.is_empty() {
+                "Not yet met".to_owned()
+            } else {
+                format!(
+                    "✅ Met on: {}",
+                    dates.iter().map(|d| d.format("%a %b %d")).join(", ")
+                )
+            };
+            (name, value)
+        })
+    }
+
+    pub fn get_visibility(&self) -> ResultsVisibility {
+        self.visibility
+            .read()
+            .unwrap()
+            .unwrap_or_else(|| ResultsVisibility::from_flags(self.hide_results, self.open_details))
+    }
+
+    pub fn set_visibility(
+        &self,
+        user: UserId,
+        visibility: ResultsVisibility,
+    ) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        *self.visibility.write().unwrap() = Some(visibility);
+        self.try_save()
+    }
+
+    /// How much of the results `user` can see, depending on whether they have responded
+    fn get_user_visibility(&self, user: UserId) -> Visibility {
+        let visibility = self.get_visibility();
+        if user == self.get_owner() {
+            visibility.owner
+        } else if self.responses.read().unwrap().contains_key(&user) {
+            visibility.responders
+        } else {
+            visibility.everyone
+        }
+    }
+
+    /// Whether `user` can see the number of responses on each date
+    pub fn can_see_results(&self, user: UserId) -> bool {
+        self.get_user_visibility(user) >= Visibility::Distribution
+    }
+
+    /// Whether `user` can see who is available on each date, rather than only the counts
+    pub fn can_see_details(&self, user: UserId) -> bool {
+        self.get_user_visibility(user) >= Visibility::Users
+    }
+
+    /// Explains why `user` can't see the results
+    pub fn get_hidden_results_note(&self, user: UserId) -> &'static str {
+        if self.get_visibility().responders >= Visibility::Distribution
+            && !self.responses.read().unwrap().contains_key(&user)
+        {
+            "Results are visible once you have responded"
+        } else {
+            "Results are only visible to the scheduler owner"
+        }
+    }
+
+    /// Short overview of the results, without listing responders. Only the number of responses
+    /// is included unless `include_results` is set.
+    pub fn get_summary(&self, include_results: bool) -> String {
+        let mut lines = vec![format!(
+            "**{}** in {}",
+            self.title,
+            self.get_channel_name(self.get_channel())
+        )];
+        {
+            let responses = self.responses.read().unwrap();
+            let declined = responses.values().filter(|r| r.declined).count();
+            let mut line = format!(
+                "Responded: {} ({} available",
+                self.get_response_count(&responses),
+                Self::get_available_count(&responses)
+            );
+            if declined > 0 {
+                line += &format!(", {} declined", declined);
+            }
+            line += ")";
+            lines.push(line);
+        }
+        if self.is_locked() {
+            lines.push(self.text(Text::ResponsesLocked).to_owned());
+        }
+        if !include_results {
+            return lines.join("\n");
+        }
+        if let Some((name, value)) = self.get_quorum_field() {
+            lines.push(format!("{}: {}", name, value));
+        }
+        let top = self
+            .get_tally()
+            .into_iter()
+            .filter(|t| t.count() > 0)
+            .sorted_by_key(|t| (std::cmp::Reverse(t.count()), t.date))
+            .take(SUMMARY_DATES)
+            .map(|t| format!("`{}:` {}", t.date.format("%a %Y-%m-%d"), t.count()))
+            .collect_vec();
+        if top.is_empty() {
+            lines.push("No dates selected yet".to_owned());
+        } else {
+            lines.push("Top dates:".to_owned());
+            lines.extend(top);
+        }
+        lines.join("\n")
+    }
+
+    /// The results as markdown tables in code blocks, one per message, with a row for each
+    /// responder if `detailed` or only the counts otherwise. Dates are split across several
+    /// tables if there are many of them, and rows across several messages if the table is too
+    /// long for one.
+    pub async fn get_results_tables(
+        &self,
+        ctx: &Context,
+        guild: Option<GuildId>,
+        detailed: bool,
+    ) -> Vec<String> {
+        let results = self.get_tally();
+        let mut names = vec![];
+        if detailed {
+            let users = self.responses.read().unwrap().keys().cloned().collect_vec();
+            for user_id in users {
+                let name = match guild {
+                    Some(guild) => guild
+                        .member(ctx, user_id)
+                        .await
+                        .map(|m| m.display_name().into_owned())
+                        .ok(),
+                    None => None,
+                };
+                let name = name.unwrap_or_else(|| user_id.to_string());
+                names.push((
+                    user_id,
+                    name.chars().take(TABLE_NAME_LENGTH).collect::<String>(),
+                ));
+            }
+            names.sort_by_key(|(_, name)| name.to_lowercase());
+        }
+
+        let mut messages = vec![];
+        for tallies in results.chunks(TABLE_DATES) {
+            let header = format!(
+                "| {} | {} |\n|---|{}\n",
+                if detailed { "Name" } else { "" },
+                tallies
+                    .iter()
+                    .map(|t| t.date.format("%a %b %d"))
+                    .join(" | "),
+                "---|".repeat(tallies.len())
+            );
+            let rows = names
+                .iter()
+                .map(|(user_id, name)| {
+                    let cells = tallies
+                        .iter()
+                        .map(|t| {
+                            if t.is_available(user_id) {
+                                "✅"
+                            } else {
+                                "❌"
+                            }
+                        })
+                        .join(" | ");
+                    format!("| {} | {} |\n", name.replace('|', "/"), cells)
+                })
+                .chain(Some(format!(
+                    "| {} | {} |\n",
+                    if detailed { "Total" } else { "Available" },
+                    tallies.iter().map(|t| t.count()).join(" | ")
+                )));
+            let mut table = header.clone();
+            for row in rows {
+                // Leave room for the code block markers
+                if table.len() + row.len() > 2000 - 8 {
+                    messages.push(format!("```\n{}```", table));
+                    table = header.clone();
                 }
-            })
-            .collect();
-        let max = results
+                table += &row;
+            }
+            messages.push(format!("```\n{}```", table));
+        }
+        messages
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Channel the scheduler was created in
+    pub fn get_channel(&self) -> ChannelId {
+        self.get_message().channel_id
+    }
+
+    fn get_results(&self, detailed: bool, bars: bool) -> impl Iterator<Item = String> + '_ {
+        let results = self.get_tally();
+        let threads = self.threads.read().unwrap().clone();
+        let organizers = self.organizers.read().unwrap().clone();
+        let max = Self::get_max_count(&results);
+        let highlight = self.highlight;
+        let tied = results
             .iter()
-            .map(|(_, users)| users.len())
-            .max()
-            .unwrap_or(0);
+            .filter(|t| max > 0 && t.count() == max)
+            .map(|t| t.date)
+            .collect_vec();
+        let picked = self.tie_break.read().unwrap().pick(&tied);
+        let soonest = self
+            .quorum
+            .filter(|_| self.mark_soonest)
+            .and_then(|quorum| results.iter().find(|t| t.count() >= quorum))
+            .map(|t| t.date);
+        let confirmed: Option<HashMap<NaiveDate, usize>> = self.get_shortlist().map(|shortlist| {
+            let responses = self.responses.read().unwrap();
+            shortlist
+                .into_iter()
+                .map(|date| {
+                    let count = responses
+                        .values()
+                        .filter(|r| r.confirmed.contains(&date))
+                        .count();
+                    (date, count)
+                })
+                .collect()
+        });
+        let month = |date: NaiveDate| (date.year(), date.month());
+        // Headings are only worth the space when there is more than one month to tell apart
+        let month_headers = !self.hide_month_headers.load(Ordering::Relaxed)
+            && results
+                .first()
+                .zip(results.last())
+                .is_some_and(|(first, last)| month(first.date) != month(last.date));
+        let header_format = match (results.first(), results.last()) {
+            (Some(first), Some(last)) if first.date.year() != last.date.year() => "**%B %Y**",
+            _ => "**%B**",
+        };
+        let mut current_month = None;
         results
             .iter()
-            .map(move |(date, users)| {
-                let count = users.len();
-                let date = date.format("%a %Y-%m-%d");
-                let mut line = if max > 0 && count == max {
-                    format!("__`{}:`__ {}", date, count)
-                } else {
-                    format!("`{}:` {}", date, count)
-                };
-                if detailed && !users.is_empty() {
+            .map(move |tally| {
+                let count = tally.count();
+                let date = tally.date.format("%a %Y-%m-%d");
+                let mut line =
+                    if picked.is_some_and(|d| d != tally.date) && tied.contains(&tally.date) {
+                        // Tied dates that weren't picked are only marked subtly
+                        format!("`{}:` *{}*", date, count)
+                    } else if max > 0 && count == max {
+                        format!("{} {}", highlight.format(date), count)
+                    } else {
+                        format!("`{}:` {}", date, count)
+                    };
+                if soonest == Some(tally.date) {
+                    line = format!("⏩ {}", line);
+                }
+                if bars && count > 0 {
+                    // Round up so that every date with a response gets a visible bar
+                    let width = (count * BAR_WIDTH).div_ceil(max);
+                    line = format!("{} {}", line, "█".repeat(width));
+                }
+                if !tally.flexible.is_empty() {
+                    line = format!(
+                        "{} ({} available + {} flexible)",
+                        line,
+                        count - tally.flexible.len(),
+                        tally.flexible.len()
+                    );
+                }
+                if let Some(count) = confirmed.as_ref().and_then(|c| c.get(&tally.date)) {
+                    line = format!("{} ✅ {} confirmed", line, count);
+                }
+                if !tally.blocks.is_empty() {
+                    line = format!(
+                        "{} ({})",
+                        line,
+                        tally
+                            .blocks
+                            .iter()
+                            .map(|(role_id, size)| format!("+{} via <@&{}>", size, role_id))
+                            .join(", ")
+                    );
+                }
+                if detailed {
+                    if let Some(thread) = threads.get(&tally.date) {
+                        line = format!("{} <#{}>", line, thread);
+                    }
+                }
+                if detailed && !tally.users.is_empty() {
                     line = format!(
                         "{} - {}",
                         line,
-                        users
+                        tally
+                            .users
                             .iter()
                             .sorted()
-                            .map(|uid| format!("<@{}>", uid))
+                            .map(|uid| {
+                                let mention = if organizers.contains(uid) {
+                                    format!("<@{}> (organizer)", uid)
+                                } else {
+                                    format!("<@{}>", uid)
+                                };
+                                match tally.conditional.get(uid) {
+                                    Some(dependency) => {
+                                        format!("{} (with <@{}>)", mention, dependency)
+                                    }
+                                    None => mention,
+                                }
+                            })
                             .join(", ")
                     );
                 }
-                line
+                (tally.date, line)
             })
+            .flat_map(move |(date, line)| {
+                let header = Some(date.format(header_format).to_string())
+                    .filter(|_| month_headers && current_month != Some(month(date)));
+                current_month = Some(month(date));
+                header.into_iter().chain(std::iter::once(line))
+            })
+            .chain(
+                // The public list of responders is shortened when it's too long
+                Some(self.get_responder_mentions())
+                    .filter(|_| detailed && self.get_responses().1)
+                    .into_iter()
+                    .flat_map(|mentions| {
+                        mentions
+                            .chunks(MENTIONS_PER_LINE)
+                            .map(|chunk| format!("Responded: {}", chunk.join(", ")))
+                            .collect_vec()
+                    }),
+            )
+            .chain(
+                Some(self.get_declined())
+                    .filter(|declined| detailed && !declined.is_empty())
+                    .map(|declined| {
+                        format!(
+                            "Declined: {}",
+                            declined.iter().map(|uid| format!("<@{}>", uid)).join(", ")
+                        )
+                    }),
+            )
             .collect_vec()
             .into_iter()
     }
 
     pub async fn update_messages(&self, ctx: &Context) {
-        self.update_message(ctx, &self.message).await;
+        let interval = EDIT_INTERVAL.get().copied().unwrap_or_default();
+        let wait = {
+            let mut last_edit = self.last_edit.write().unwrap();
+            let now = Instant::now();
+            match *last_edit {
+                Some(t) if now < t + interval => Some(t + interval - now),
+                _ => {
+                    *last_edit = Some(now);
+                    None
+                }
+            }
+        };
+        if let Some(wait) = wait {
+            // Updates made during the interval are coalesced into the one that's waiting
+            if self.edit_pending.swap(true, Ordering::Relaxed) {
+                return;
+            }
+            tokio::time::sleep(wait).await;
+            *self.last_edit.write().unwrap() = Some(Instant::now());
+            self.edit_pending.store(false, Ordering::Relaxed);
+        }
+        let mut result = self.update_message(ctx, &self.get_message()).await;
         let repost = *self.repost_message.read().unwrap();
         if let Some(message) = repost {
-            self.update_message(ctx, &message).await;
+            result = result.and(self.update_message(ctx, &message).await);
+        }
+        match result {
+            Ok(()) => self.edit_failures.store(0, Ordering::Relaxed),
+            Err(e) => {
+                let failures = self.edit_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failures == EDIT_FAILURE_LIMIT {
+                    self.notify_edit_failure(ctx, &e).await;
+                }
+            }
+        }
+    }
+
+    /// Lets the owner know their scheduler is stuck, since otherwise the failure is only logged
+    async fn notify_edit_failure(&self, ctx: &Context, e: &serenity::Error) {
+        info!("notifying owner of edit failures: {}", self.get_id());
+        let content = format!(
+            "Your scheduler **{}** in {} can't be updated ({}). Check that the bot can view the \
+             channel and send messages and embeds there, or use `/schedule repost` to post a \
+             copy somewhere else.",
+            self.title,
+            self.get_channel_name(self.get_message().channel_id),
+            e
+        );
+        let result = match self.get_owner().create_dm_channel(ctx).await {
+            Ok(channel) => channel.say(ctx, content).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Cannot notify owner: {}", e);
+        }
+    }
+
+    /// Whether the detailed results are shown on the scheduler message, which names responders and
+    /// so is only allowed if everyone may see who is available
+    fn shows_public_details(&self) -> bool {
+        self.public_details.load(Ordering::Relaxed)
+            && self.get_visibility().everyone >= Visibility::Users
+    }
+
+    /// Values of the results fields on the scheduler message. Detailed results are split across
+    /// fields, and fall back to the summary if they still don't fit.
+    fn get_public_results(&self) -> Vec<String> {
+        if self.shows_public_details() {
+            let mut fields = vec![String::new()];
+            for line in self.get_results(true, self.bars) {
+                let field = fields.last_mut().unwrap();
+                if field.is_empty() {
+                    *field = line;
+                } else if field.chars().count() + line.chars().count() < FIELD_LIMIT {
+                    field.push('\n');
+                    field.push_str(&line);
+                } else {
+                    fields.push(line);
+                }
+            }
+            if fields.len() <= MAX_RESULT_FIELDS
+                && fields.iter().all(|f| f.chars().count() <= FIELD_LIMIT)
+            {
+                return fields;
+            }
         }
+        let mut results = self.get_results(false, self.bars).join("\n");
+        // The bars are only decoration, so drop them rather than overflow the field
+        if self.bars && results.chars().count() > FIELD_LIMIT {
+            results = self.get_results(false, false).join("\n");
+        }
+        vec![results]
     }
 
-    async fn update_message(&self, ctx: &Context, message: &MessageShim) {
+    async fn update_message(&self, ctx: &Context, message: &MessageShim) -> serenity::Result<()> {
         let title = &self.title;
-        let responses = self.get_responses();
-        let results = self.get_results(false).join("\n");
+        let (responses, _) = self.get_responses();
+        let results = self.get_public_results();
+        let hide_results = self.get_visibility().everyone < Visibility::Distribution;
+        let quorum = self.get_quorum_field().filter(|_| !hide_results);
+        let target = self.get_target_field();
+        let leading = self.get_leading_field().filter(|_| !hide_results);
         let closed = self.closed;
+        let time_ranges = self.time_ranges.load(Ordering::Relaxed);
+        let locked = self.is_locked();
+        let closed_to_new = self.is_closed_to_new();
+        let opens_at = self
+            .get_opens_at()
+            .filter(|_| !closed)
+            .map(|t| format!("Responses open <t:{}:R>", t.timestamp()));
         let content = match &self.group {
             Some(role) => format!("<@&{}>", role),
             None => "".to_owned(),
         };
+        // Mentions aren't rendered in embed footers, so look up the owner's name instead
+        let cached = self.owner_name.read().unwrap().clone();
+        let owner = match cached {
+            Some(name) => name,
+            None => match self.get_owner().to_user(ctx).await {
+                Ok(user) => {
+                    *self.owner_name.write().unwrap() = Some(user.name.clone());
+                    user.name
+                }
+                Err(_) => self.get_owner().to_string(),
+            },
+        };
+        // The embed timestamp shows when the message was last edited, so that responders can
+        // tell that it reflects their response, and the creation date moves into the footer text
+        let footer = format!(
+            "Created by {} on {} • Last updated",
+            owner,
+            self.get_created_at().format("%b %d, %Y")
+        );
+        let mut notes = self.notes.read().unwrap().as_deref().map(escape_markdown);
+        let blackout_reason = self
+            .blackout_reason
+            .read()
+            .unwrap()
+            .clone()
+            .filter(|_| !self.blackout_dates.read().unwrap().is_empty());
+        if let Some(reason) = blackout_reason {
+            let line = format!("*Some dates excluded: {}*", reason);
+            notes = Some(match notes {
+                Some(notes) => format!("{}\n\n{}", notes, line),
+                None => line,
+            });
+        }
+        let event = self.event.read().unwrap().map(|e| {
+            format!(
+                "[{}](https://discord.com/events/{}/{})",
+                e.date.format("%a %b %d"),
+                e.guild_id,
+                e.event_id
+            )
+        });
         message
             .edit(ctx, |m| {
                 let mut ar = CreateActionRow::default();
                 let mut text = "";
                 if !closed {
-                    ar.create_button(|b| b.label("Add response").custom_id("response"));
+                    ar.create_button(|b| {
+                        b.label(self.text(Text::AddResponse))
+                            .custom_id("response")
+                            .disabled(opens_at.is_some())
+                    });
+                    if time_ranges {
+                        ar.create_button(|b| {
+                            b.style(ButtonStyle::Secondary)
+                                .label(self.text(Text::SetTimes))
+                                .custom_id("times")
+                                .disabled(opens_at.is_some())
+                        });
+                    }
                     ar.create_button(|b| {
                         b.style(ButtonStyle::Secondary)
-                            .label("Show details")
+                            .label(self.text(Text::ShowDetails))
                             .custom_id("details")
                     });
                 } else {
                     ar.create_button(|b| {
                         b.style(ButtonStyle::Secondary)
-                            .label("Show details")
+                            .label(self.text(Text::ShowDetails))
                             .custom_id("details")
                     });
-                    text = "Final results";
+                    text = self.text(Text::FinalResults);
+                }
+                if !closed && locked {
+                    text = self.text(Text::ResponsesLocked);
+                } else if let Some(opens_at) = &opens_at {
+                    text = opens_at;
+                } else if !closed && closed_to_new {
+                    text = self.text(Text::ClosedToNew);
                 }
+                let description = match notes {
+                    Some(notes) if text.is_empty() => notes,
+                    Some(notes) => format!("**{}**\n\n{}", text, notes),
+                    None => text.to_owned(),
+                };
                 m.content(content)
                     .embed(|e| {
-                        e.title(title)
-                            .description(text)
-                            .field("Responded", responses, false)
-                            .field("Results", &results, true)
+                        e.title(title).description(description).field(
+                            self.text(Text::Responded),
+                            responses,
+                            false,
+                        );
+                        if let Some(target) = target {
+                            e.field(self.text(Text::Goal), target, false);
+                        }
+                        if let Some(leading) = leading {
+                            e.field(self.text(Text::Leading), leading, false);
+                        }
+                        if let Some((name, value)) = quorum {
+                            e.field(name, value, false);
+                        }
+                        if let Some(event) = event {
+                            e.field("Event", event, false);
+                        }
+                        if !hide_results {
+                            let inline = results.len() == 1;
+                            for (i, value) in results.iter().enumerate() {
+                                let name = match i {
+                                    0 => self.text(Text::Results),
+                                    _ => self.text(Text::ResultsContinued),
+                                };
+                                e.field(name, value, inline);
+                            }
+                        }
+                        e.footer(|f| f.text(footer)).timestamp(Utc::now())
                     })
                     .components(|c| c.add_action_row(ar))
                     .allowed_mentions(|am| am.roles(self.group))
                     .suppress_embeds(false)
             })
             .await
-            .map_err(|e| error!("Cannot edit message: {}", e))
-            .ok();
+            .map_err(|e| {
+                error!("Cannot edit message: {}", e);
+                e
+            })
+    }
+
+    #[instrument(skip_all, fields(scheduler = %self.get_id(), user = %component.user.id))]
+    pub async fn show_details(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        component.defer(ctx).await.unwrap();
+        if !self.can_see_results(component.user.id) {
+            component
+                .create_followup_message(ctx, |m| {
+                    m.ephemeral(true)
+                        .content(self.get_hidden_results_note(component.user.id))
+                })
+                .await
+                .expect("Cannot send message");
+            return;
+        }
+        let detailed = self.can_see_details(component.user.id);
+        // Reasons are private even when everyone can see the details
+        let decline_reasons = Some(self.get_decline_reasons())
+            .filter(|_| component.user.id == self.get_owner())
+            .unwrap_or_default();
+        let results = self
+            .get_results(detailed, self.bars)
+            .chain(decline_reasons)
+            .chain(self.get_weekday_averages())
+            .chain(self.get_coverage())
+            .chain(self.get_time_windows());
+        let mut messages = split_messages(results);
+        let last_content = messages.pop().unwrap_or_default();
+        for content in messages {
+            component
+                .create_followup_message(ctx, |m| m.ephemeral(true).content(content))
+                .await
+                .expect("Cannot send message");
+        }
+        component
+            .create_followup_message(ctx, |m| {
+                if component.user.id == self.get_owner() {
+                    m.components(|c| self.create_owner_buttons(c));
+                }
+                m.ephemeral(true).content(last_content)
+            })
+            .await
+            .expect("Cannot send message");
+    }
+
+    /// Owner tools shown below the details
+    fn create_owner_buttons<'a>(
+        &self,
+        components: &'a mut CreateComponents,
+    ) -> &'a mut CreateComponents {
+        let mut ar = CreateActionRow::default();
+        ar.create_button(|b| b.label("Add blackout dates").custom_id("blackout"));
+        ar.create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Create event")
+                .custom_id("event")
+        });
+        if !self.closed {
+            ar.create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label("Send reminder")
+                    .custom_id("remind")
+            });
+            let (label, id) = if self.is_locked() {
+                ("Unlock responses", "unlock")
+            } else {
+                ("Lock responses", "lock")
+            };
+            ar.create_button(|b| b.style(ButtonStyle::Secondary).label(label).custom_id(id));
+        }
+        // Responders can't be named publicly unless everyone may see them
+        if self.get_visibility().everyone >= Visibility::Users {
+            let (label, id) = if self.public_details.load(Ordering::Relaxed) {
+                ("Hide public details", "private_details")
+            } else {
+                ("Show details publicly", "public_details")
+            };
+            ar.create_button(|b| b.style(ButtonStyle::Secondary).label(label).custom_id(id));
+        }
+        components.add_action_row(ar);
+
+        let mut ar = CreateActionRow::default();
+        ar.create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Create discussion threads")
+                .custom_id("threads")
+        });
+        ar.create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Remove departed members")
+                .custom_id("prune")
+        });
+        ar.create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Edit notes")
+                .custom_id("notes")
+        });
+        if !self.closed {
+            let (label, id) = if self.is_closed_to_new() {
+                ("Reopen to new responders", "open_new")
+            } else {
+                ("Close to new responders", "close_new")
+            };
+            ar.create_button(|b| b.style(ButtonStyle::Secondary).label(label).custom_id(id));
+        }
+        #[cfg(feature = "heatmap")]
+        ar.create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label("Show chart")
+                .custom_id("chart")
+        });
+        components.add_action_row(ar);
+
+        let mut ar = CreateActionRow::default();
+        let (label, id) = if self.pinned.load(Ordering::Relaxed) {
+            ("Unpin message", "unpin")
+        } else {
+            ("Pin message", "pin")
+        };
+        ar.create_button(|b| b.style(ButtonStyle::Secondary).label(label).custom_id(id));
+        components.add_action_row(ar)
+    }
+
+    /// Pins or unpins the original scheduler message. Only messages that the bot pinned itself
+    /// are unpinned.
+    pub async fn set_pinned(&self, ctx: &Context, pinned: bool) -> Result<(), SchedulerError> {
+        if pinned {
+            match self.get_message().pin(ctx).await {
+                Err(serenity::Error::Http(e))
+                    if matches!(&*e, HttpError::UnsuccessfulRequest(r)
+                        if r.error.code == MAX_PINS_REACHED) =>
+                {
+                    return Err(SchedulerError::PinLimit)
+                }
+                result => result?,
+            }
+        } else if self.pinned.load(Ordering::Relaxed) {
+            self.get_message().unpin(ctx).await?;
+        }
+        self.pinned.store(pinned, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    pub async fn handle_set_pinned(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        pinned: bool,
+    ) {
+        component.defer(ctx).await.unwrap();
+        let result = if component.user.id != self.get_owner() {
+            Err(SchedulerError::NotOwner)
+        } else {
+            self.set_pinned(ctx, pinned).await
+        };
+        let text = match result {
+            Ok(()) if pinned => "Message pinned".to_owned(),
+            Ok(()) => "Message unpinned".to_owned(),
+            Err(e) => self.error_text(&e),
+        };
+        component
+            .create_followup_message(ctx, |m| m.ephemeral(true).content(text))
+            .await
+            .expect("Cannot send message");
+    }
+
+    /// Creates a discussion thread for each of the most popular dates that doesn't have one yet
+    pub async fn create_threads(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        component.defer(ctx).await.unwrap();
+        if component.user.id != self.get_owner() {
+            component
+                .create_followup_message(ctx, |m| {
+                    m.ephemeral(true)
+                        .content("Only the scheduler owner may create threads")
+                })
+                .await
+                .expect("Cannot send message");
+            return;
+        }
+
+        let existing: HashSet<NaiveDate> = self.threads.read().unwrap().keys().cloned().collect();
+        let dates = self
+            .get_tally()
+            .into_iter()
+            .filter(|t| t.count() > 0 && !existing.contains(&t.date))
+            .sorted_by_key(|t| (std::cmp::Reverse(t.count()), t.date))
+            .take(MAX_THREADS.saturating_sub(existing.len()))
+            .map(|t| t.date)
+            .collect_vec();
+        let channel_id = match self.get_thread_channel(ctx).await {
+            Ok(channel_id) => channel_id,
+            Err(msg) => {
+                component
+                    .create_followup_message(ctx, |m| m.ephemeral(true).content(msg))
+                    .await
+                    .expect("Cannot send message");
+                return;
+            }
+        };
+        let mut created = 0;
+        let mut error = None;
+        for date in dates {
+            let title: String = self.title.chars().take(80).collect();
+            let result = channel_id
+                .create_private_thread(ctx, |t| {
+                    t.name(format!("{} - {}", title, date.format("%a %b %d")))
+                        .kind(ChannelType::PublicThread)
+                })
+                .await;
+            match result {
+                Ok(thread) => {
+                    self.threads.write().unwrap().insert(date, thread.id);
+                    created += 1;
+                }
+                Err(e) => {
+                    error!("Cannot create thread: {}", e);
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+        if created > 0 {
+            self.save();
+        }
+
+        let text = match error {
+            Some(e) => format!(
+                "Created {} threads, but couldn't create the rest; this channel may not support \
+                 threads ({})",
+                created, e
+            ),
+            None if created == 0 => "No new dates to create threads for".to_owned(),
+            None => format!(
+                "Created {} threads; see \"Show details\" for links",
+                created
+            ),
+        };
+        component
+            .create_followup_message(ctx, |m| m.ephemeral(true).content(text))
+            .await
+            .expect("Cannot send message");
+    }
+
+    /// Sends a PNG chart of each responder's availability, or an explanation if there are too
+    /// many responders to show one
+    #[cfg(feature = "heatmap")]
+    pub async fn show_chart(&self, ctx: &Context, component: &MessageComponentInteraction) {
+        component.defer(ctx).await.unwrap();
+        let tally = self.get_tally();
+        let users = self
+            .responses
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .sorted()
+            .collect_vec();
+        if users.is_empty() || users.len() > MAX_CHART_USERS {
+            let text = if users.is_empty() {
+                "No responses yet".to_owned()
+            } else {
+                format!(
+                    "Too many responders for a chart (at most {}); use \"Show details\" instead",
+                    MAX_CHART_USERS
+                )
+            };
+            component
+                .create_followup_message(ctx, |m| m.ephemeral(true).content(text))
+                .await
+                .expect("Cannot send message");
+            return;
+        }
+
+        let rows = users
+            .iter()
+            .map(|user| tally.iter().map(|t| t.is_available(user)).collect())
+            .collect_vec();
+        let data = tokio::task::spawn_blocking(move || crate::chart::render_heatmap(&rows))
+            .await
+            .expect("Chart task failed");
+        let data = match data {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Cannot render chart: {}", e);
+                return;
+            }
+        };
+        let legend = format!(
+            "Columns: {}\nRows: {}",
+            tally.iter().map(|t| t.date.format("%b %d")).join(", "),
+            users
+                .iter()
+                .enumerate()
+                .map(|(i, user)| format!("{}. <@{}>", i + 1, user))
+                .join(", ")
+        );
+        component
+            .create_followup_message(ctx, |m| {
+                m.ephemeral(true)
+                    .content(legend)
+                    .allowed_mentions(|am| am.empty_users())
+                    .add_file(AttachmentType::Bytes {
+                        data: data.into(),
+                        filename: "availability.png".to_owned(),
+                    })
+            })
+            .await
+            .expect("Cannot send message");
+    }
+
+    /// Locks or unlocks responses on behalf of `user`, without updating the messages
+    fn set_locked(&self, user: UserId, locked: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        if self.closed {
+            return Err(SchedulerError::Closed);
+        }
+        self.locked.store(locked, Ordering::Relaxed);
+        *self.locked_at.write().unwrap() = locked.then(Instant::now);
+        self.try_save()
+    }
+
+    pub async fn handle_set_locked(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        locked: bool,
+    ) {
+        let result = self.set_locked(component.user.id, locked);
+        let text = match &result {
+            Ok(()) if locked => self.text(Text::ResponsesLocked).to_owned(),
+            Ok(()) => self.text(Text::ResponsesUnlocked).to_owned(),
+            Err(e) => self.error_text(e),
+        };
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        if result.is_ok() || matches!(result, Err(SchedulerError::Persistence(_))) {
+            self.update_messages(ctx).await;
+        }
+    }
+
+    /// Stops or resumes accepting responses from users who haven't responded yet
+    fn set_closed_to_new(&self, user: UserId, closed: bool) -> Result<(), SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
+        }
+        if self.closed {
+            return Err(SchedulerError::Closed);
+        }
+        self.closed_to_new.store(closed, Ordering::Relaxed);
+        self.try_save()
+    }
+
+    pub async fn handle_set_closed_to_new(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        closed: bool,
+    ) {
+        let result = self.set_closed_to_new(component.user.id, closed);
+        let text = match &result {
+            Ok(()) if closed => "Closed to new responders".to_owned(),
+            Ok(()) => "Reopened to new responders".to_owned(),
+            Err(e) => self.error_text(e),
+        };
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        if result.is_ok() || matches!(result, Err(SchedulerError::Persistence(_))) {
+            self.update_messages(ctx).await;
+        }
+    }
+
+    pub async fn set_public_details(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        enabled: bool,
+    ) {
+        let is_owner = component.user.id == self.get_owner();
+        let hidden = enabled && self.get_visibility().everyone < Visibility::Users;
+        let allowed = is_owner && !hidden;
+        let text = if !is_owner {
+            "Only the scheduler owner may change how results are shown"
+        } else if hidden {
+            "Detailed results can only be shown publicly if everyone can see who is available"
+        } else if enabled {
+            "Detailed results are now shown on the scheduler message"
+        } else {
+            "Detailed results are no longer shown on the scheduler message"
+        };
+        if allowed {
+            self.public_details.store(enabled, Ordering::Relaxed);
+            self.save();
+        }
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
+        if allowed {
+            self.update_messages(ctx).await;
+        }
+    }
+
+    pub async fn send_reminder(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        notifier: &Notifier,
+    ) {
+        let text = if component.user.id != self.get_owner() {
+            "Only the scheduler owner may send reminders"
+        } else if self.closed {
+            "Scheduler is closed"
+        } else {
+            // Reply to the most recent copy, since that's the one most likely to be seen
+            let message = self
+                .repost_message
+                .read()
+                .unwrap()
+                .unwrap_or(self.get_message());
+            let content = match self.group {
+                Some(role) => format!(
+                    "<@&{}> Reminder: please respond to **{}**",
+                    role, self.title
+                ),
+                None => format!("Reminder: please respond to **{}**", self.title),
+            };
+            notifier.send(Notification {
+                recipient: Recipient::Channel(message.channel_id),
+                reply_to: Some(message.message_id),
+                content,
+                mention_role: self.group,
+            });
+            "Reminder queued"
+        };
+        component
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|m| m.content(text).ephemeral(true))
+            })
+            .await
+            .expect("Cannot send response");
     }
 
-    pub async fn show_details(&self, ctx: &Context, component: &MessageComponentInteraction) {
-        component.defer(ctx).await.unwrap();
-        let results = self.get_results(true);
-        let mut messages: Vec<String> = vec![];
-        let mut content = String::new();
-        for line in results {
-            assert!(line.len() < 2000);
-            if content.len() + line.len() >= 2000 {
-                messages.push(content);
-                content = String::new()
-            }
-            content += &line;
-            content.push('\n');
-        }
-        let last_content = content;
-        for content in messages {
-            component
-                .create_followup_message(ctx, |m| m.ephemeral(true).content(content))
-                .await
-                .expect("Cannot send message");
+    /// DMs the responders whose response was last submitted more than `older_than` ago, asking
+    /// them to submit it again. If `clear_after` is given, the responses of those who haven't
+    /// by then are removed. Returns the number of responders nudged.
+    #[allow(clippy::too_many_arguments)]
+    pub fn nudge_stale_responders(
+        self: Arc<Self>,
+        ctx: &Context,
+        notifier: &Notifier,
+        user: UserId,
+        guild: GuildId,
+        older_than: Duration,
+        clear_after: Option<std::time::Duration>,
+    ) -> Result<usize, SchedulerError> {
+        if user != self.get_owner() {
+            return Err(SchedulerError::NotOwner);
         }
-        component
-            .create_followup_message(ctx, |m| {
-                if component.user.id == self.owner {
-                    let mut ar = CreateActionRow::default();
-                    ar.create_button(|b| b.label("Add blackout dates").custom_id("blackout"));
-                    m.components(|c| c.add_action_row(ar));
-                }
-                m.ephemeral(true).content(last_content)
-            })
-            .await
-            .expect("Cannot send message");
+        if self.closed {
+            return Err(SchedulerError::Closed);
+        }
+        let now = Utc::now();
+        // Responses saved before submission times were recorded count as stale
+        let stale: HashMap<UserId, Option<DateTime<Utc>>> = self
+            .responses
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, r)| r.submitted_at.is_none_or(|t| now - t > older_than))
+            .map(|(user, r)| (*user, r.submitted_at))
+            .collect();
+        let message = self
+            .repost_message
+            .read()
+            .unwrap()
+            .unwrap_or(self.get_message());
+        let link = format!(
+            "https://discord.com/channels/{}/{}/{}",
+            guild, message.channel_id, message.message_id
+        );
+        let warning = match clear_after {
+            Some(clear_after) => format!(
+                " Responses that aren't submitted again within {} hours will be removed.",
+                clear_after.as_secs() / 3600
+            ),
+            None => String::new(),
+        };
+        for (user, submitted_at) in &stale {
+            let age = match submitted_at {
+                Some(t) => format!("{} days ago", (now - *t).num_days()),
+                None => "a while ago".to_owned(),
+            };
+            notifier.send(Notification {
+                recipient: Recipient::User(*user),
+                reply_to: None,
+                content: format!(
+                    "Your response to **{}** was last updated {}. Please check that it's still \
+                     right and submit it again: {}{}",
+                    self.title, age, link, warning
+                ),
+                mention_role: None,
+            });
+        }
+        info!(
+            "nudged {} stale responders of {}",
+            stale.len(),
+            self.get_id()
+        );
+        let count = stale.len();
+        if let (Some(clear_after), false) = (clear_after, stale.is_empty()) {
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(clear_after).await;
+                self.clear_stale_responses(&ctx, &stale).await;
+            });
+        }
+        Ok(count)
     }
 
-    pub async fn get_response(
+    /// Removes the responses in `stale` that haven't been submitted again since
+    async fn clear_stale_responses(
         &self,
         ctx: &Context,
-        component: &MessageComponentInteraction,
+        stale: &HashMap<UserId, Option<DateTime<Utc>>>,
+    ) {
+        if self.closed {
+            return;
+        }
+        let removed = {
+            let mut responses = self.responses.write().unwrap();
+            let count = responses.len();
+            responses.retain(|user, r| stale.get(user).is_none_or(|t| r.submitted_at != *t));
+            count - responses.len()
+        };
+        if removed > 0 {
+            info!("removed {} stale responses from {}", removed, self.get_id());
+            self.save();
+            self.update_messages(ctx).await;
+        }
+    }
+
+    /// If enabled, returns a one-time DM letting a user who reacted to the scheduler message
+    /// know that reactions aren't counted as responses
+    pub fn get_reaction_nudge(&self, user: UserId, guild: GuildId) -> Option<Notification> {
+        if !self.reaction_nudge || self.closed || self.responses.read().unwrap().contains_key(&user)
+        {
+            return None;
+        }
+        if !self.nudged.write().unwrap().insert(user) {
+            return None;
+        }
+        self.save();
+        let message = self
+            .repost_message
+            .read()
+            .unwrap()
+            .unwrap_or(self.get_message());
+        let content = format!(
+            "You reacted to **{}**; did you mean to respond? Reactions aren't counted, so use the \
+             \"Add response\" button to submit your availability: \
+             https://discord.com/channels/{}/{}/{}",
+            self.title, guild, message.channel_id, message.message_id
+        );
+        Some(Notification {
+            recipient: Recipient::User(user),
+            reply_to: None,
+            content,
+            mention_role: None,
+        })
+    }
+
+    /// Whether the user of `session` may respond on behalf of everyone with `role`
+    pub async fn can_respond_for(&self, session: &impl ResponseSession, role: RoleId) -> bool {
+        session.user_id() == self.get_owner() || session.has_role(role).await.unwrap_or(false)
+    }
+
+    pub fn respond_for_denied(role: RoleId) -> String {
+        format!(
+            "Only the scheduler owner or members of <@&{}> may respond for it",
+            role
+        )
+    }
+
+    #[instrument(skip_all, fields(scheduler = %self.get_id(), user = %session.user_id()))]
+    pub async fn get_response(
+        &self,
+        host: &impl EditorHost,
+        session: &mut impl ResponseSession,
         resp_type: ResponseType,
+        profiles: &Profiles,
     ) {
-        let user = &component.user;
+        let user_id = session.user_id();
+        let started = Instant::now();
 
-        if let Some(role) = self.group {
-            let guild = component.guild_id.expect("Cannot get guild");
-            let allowed = user
-                .has_role(&ctx, guild, role)
-                .await
-                .expect("Cannot check role");
+        if let ResponseType::Block(role, _) = resp_type {
+            if !self.can_respond_for(session, role).await {
+                session
+                    .respond(
+                        Some(Self::respond_for_denied(role)),
+                        CreateComponents::default(),
+                    )
+                    .await
+                    .expect("Cannot send response");
+                return;
+            }
+        } else if let Some(role) = self.group {
+            let allowed = session.has_role(role).await.expect("Cannot check role");
             if !allowed {
-                component
-                    .create_interaction_response(&ctx, |r| {
-                        r.kind(InteractionResponseType::ChannelMessageWithSource)
-                            .interaction_response_data(|m| {
-                                m.content(format!("Only <@&{}> may respond", role))
-                                    .ephemeral(true)
-                            })
-                    })
+                session
+                    .respond(
+                        Some(format!("Only <@&{}> may respond", role)),
+                        CreateComponents::default(),
+                    )
                     .await
                     .expect("Cannot send response");
                 return;
             }
         };
 
+        let opens_at = self
+            .get_opens_at()
+            .filter(|_| resp_type != ResponseType::Blackout);
+        if let Some(opens_at) = opens_at {
+            session
+                .respond(
+                    Some(format!("Responses open at <t:{}:f>", opens_at.timestamp())),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        if resp_type != ResponseType::Blackout && self.is_locked() {
+            session
+                .respond(
+                    Some(self.error_text(&SchedulerError::Locked)),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        if resp_type == ResponseType::Normal
+            && self.is_closed_to_new()
+            && !self.responses.read().unwrap().contains_key(&user_id)
+        {
+            session
+                .respond(
+                    Some(self.error_text(&SchedulerError::ClosedToNew)),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        if resp_type == ResponseType::Normal
+            && self.is_full(&self.responses.read().unwrap(), user_id)
+        {
+            session
+                .respond(
+                    Some(self.error_text(&SchedulerError::Full)),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        // Every date may have passed and been blacked out, and Discord rejects a menu without
+        // options
+        if resp_type != ResponseType::Blackout && self.get_available_dates().is_empty() {
+            session
+                .respond(
+                    Some(self.text(Text::NoDatesLeft).to_owned()),
+                    CreateComponents::default(),
+                )
+                .await
+                .expect("Cannot send response");
+            return;
+        }
+
+        // Once dates are shortlisted, responders only confirm which of them they can make
+        let resp_type = match resp_type {
+            ResponseType::Normal if self.get_shortlist().is_some() => ResponseType::Confirm,
+            resp_type => resp_type,
+        };
+
         let (mut response, allow_delete) = match resp_type {
             ResponseType::Normal => self
                 .responses
                 .read()
                 .unwrap()
-                .get(&user.id)
+                .get(&user_id)
                 .cloned()
                 .map(|r| (r, true))
                 .unwrap_or((Response::default(), false)),
-            ResponseType::Blackout => (self.blackout_dates.read().unwrap().clone().into(), false),
+            ResponseType::Blackout => {
+                let dates: HashSet<NaiveDate> = self
+                    .blackout_dates
+                    .read()
+                    .unwrap()
+                    .keys()
+                    .cloned()
+                    .collect();
+                (dates.into(), false)
+            }
+            ResponseType::Confirm => self
+                .responses
+                .read()
+                .unwrap()
+                .get(&user_id)
+                .map(|r| (r.confirmed.clone().into(), false))
+                .unwrap_or((Response::default(), false)),
+            ResponseType::Block(role, _) => self
+                .role_blocks
+                .read()
+                .unwrap()
+                .get(&role)
+                .map(|b| (b.dates.clone().into(), true))
+                .unwrap_or((Response::default(), false)),
         };
-        component
-            .create_interaction_response(ctx, |r| {
-                r.kind(InteractionResponseType::ChannelMessageWithSource)
-                    .interaction_response_data(|m| {
-                        m.ephemeral(true).components(|c| {
-                            self.create_dm_buttons(&response, c, resp_type, allow_delete)
-                        })
-                    })
+        let preselected = resp_type == ResponseType::Normal
+            && !allow_delete
+            && self.preselect_all.load(Ordering::Relaxed);
+        if preselected {
+            response.dates = self.get_available_dates();
+        }
+        // The text editor waits for replies in DMs, so it can only be used with a connection
+        let text_ctx = host.context().filter(|_| {
+            resp_type == ResponseType::Normal
+                && profiles.get(user_id).is_some_and(|p| p.text_responses)
+        });
+        let token = self.start_editing(user_id);
+        let outcome = if let Some(ctx) = text_ctx {
+            match self
+                .run_text_editor(ctx, session, response, allow_delete)
+                .await
+            {
+                Some(outcome) => outcome,
+                None => {
+                    self.finish_editing(user_id, token);
+                    return;
+                }
+            }
+        } else {
+            let editor = Editor {
+                response,
+                resp_type,
+                allow_delete,
+                weekdays: self.weekday_filters.read().unwrap().get(&user_id).cloned(),
+                preselected,
+                show_responders: self.can_see_details(user_id),
+            };
+            let mut components = CreateComponents::default();
+            self.create_dm_buttons(&editor, &mut components);
+            session
+                .respond(self.get_editor_note(&editor), components)
+                .await
+                .expect("Cannot send DM");
+            self.run_editor(host, session, editor, profiles).await
+        };
+
+        // Responses may have been locked, or another user may have taken the last spot, while
+        // this one was being edited. Locking only rejects the response once the grace period has
+        // passed. If the user opened another editor in the meantime, only the newest one may
+        // change their response.
+        let superseded = !self.finish_editing(user_id, token);
+        let too_fast = matches!(outcome, EditorOutcome::Submit(_))
+            && !superseded
+            && !self.record_submit(user_id);
+        let text = match outcome {
+            EditorOutcome::Submit(_) | EditorOutcome::Delete if superseded => {
+                info!("Response superseded");
+                self.error_text(&SchedulerError::Superseded)
+            }
+            EditorOutcome::Submit(_) if too_fast => {
+                info!("Response submitted too soon");
+                self.error_text(&SchedulerError::TooFast)
+            }
+            EditorOutcome::Submit(response) => loop {
+                let result = match resp_type {
+                    ResponseType::Normal => {
+                        self.add_response(host, user_id, response.clone(), started, token)
+                            .await
+                    }
+                    ResponseType::Blackout => {
+                        self.set_blackout(host, user_id, response.clone()).await
+                    }
+                    ResponseType::Confirm => {
+                        self.set_confirmed(host, user_id, response.dates.clone(), started, token)
+                            .await
+                    }
+                    ResponseType::Block(role, size) => {
+                        self.set_role_block(host, role, size, response.clone(), started)
+                            .await
+                    }
+                };
+                match result {
+                    Ok(()) => break self.text(Text::ResponseSubmitted).to_owned(),
+                    // The response was recorded but not saved, so submitting it again from this
+                    // session only has to save it
+                    Err(e @ SchedulerError::Persistence(_)) if text_ctx.is_none() => {
+                        if !self.ask_retry(session, &e, started).await {
+                            break self.error_text(&e);
+                        }
+                        info!("Retrying submission");
+                    }
+                    Err(e) => break self.error_text(&e),
+                }
+            },
+            EditorOutcome::Delete => {
+                let result = match resp_type {
+                    ResponseType::Block(role, _) => self.delete_role_block(host, role).await,
+                    _ => self.delete_response(host, user_id).await,
+                };
+                match result {
+                    Ok(()) => self.text(Text::ResponseDeleted).to_owned(),
+                    Err(e) => self.error_text(&e),
+                }
+            }
+            EditorOutcome::TimedOut => {
+                info!("Response timed out");
+                self.text(Text::ResponseTimedOut).to_owned()
+            }
+            EditorOutcome::Cancelled => self.text(Text::ResponseCancelled).to_owned(),
+        };
+        if let Some(ctx) = text_ctx {
+            let result = match user_id.create_dm_channel(ctx).await {
+                Ok(channel) => channel.say(ctx, &text).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error!("Cannot send DM: {}", e);
+            }
+        }
+        if session
+            .edit(Some(text), CreateComponents::default())
+            .await
+            .is_err()
+        {
+            error!("Cannot update message");
+        }
+    }
+
+    /// Offers to submit a response again after it couldn't be saved, returning whether the user
+    /// chose to before the editor would have timed out
+    async fn ask_retry(
+        &self,
+        session: &mut impl ResponseSession,
+        error: &SchedulerError,
+        started: Instant,
+    ) -> bool {
+        let mut components = CreateComponents::default();
+        components.create_action_row(|ar| {
+            ar.create_button(|b| {
+                b.style(ButtonStyle::Primary)
+                    .label(self.text(Text::Submit))
+                    .custom_id("submit")
             })
+        });
+        let text = format!(
+            "{}; {}",
+            self.error_text(error),
+            self.text(Text::SubmitAgain)
+        );
+        if session.edit(Some(text), components).await.is_err() {
+            error!("Cannot update message");
+            return false;
+        }
+        let timeout = (started + RESP_TIMEOUT).saturating_duration_since(Instant::now());
+        let retry = session
+            .next_action(timeout)
             .await
-            .expect("Cannot send DM");
+            .is_some_and(|a| a.custom_id == "submit");
+        // A session opened in the meantime takes over the response
+        retry
+            && !self
+                .editing_sessions
+                .read()
+                .unwrap()
+                .contains_key(&session.user_id())
+    }
+
+    /// Alternative to the response editor for users who find the select menu hard to use, such
+    /// as with a screen reader. The dates are listed in a DM, and the user replies with the
+    /// numbers of the dates to toggle. Returns `None` if the DM can't be sent.
+    async fn run_text_editor(
+        &self,
+        ctx: &Context,
+        session: &mut impl ResponseSession,
+        mut response: Response,
+        allow_delete: bool,
+    ) -> Option<EditorOutcome> {
+        let blackout_dates = self.blackout_dates.read().unwrap().clone();
+        let dates = self
+            .dates
+            .iter()
+            .filter(|d| !blackout_dates.contains_key(d))
+            .cloned()
+            .collect_vec();
+        let mut commands =
+            "`done` to submit, `all` or `none` to select every date or none of them".to_owned();
+        if allow_delete {
+            commands += ", `delete` to delete your response";
+        }
+        let instructions = format!(
+            "Responding to **{}**. Reply with the numbers of the dates to change, separated by \
+             spaces (e.g. `1 3`), {}, or `cancel` to stop without saving.",
+            self.title, commands
+        );
+        let list = |response: &Response| {
+            dates
+                .iter()
+                .enumerate()
+                .map(|(i, date)| {
+                    let state = if response.dates.contains(date) {
+                        "available"
+                    } else {
+                        "not available"
+                    };
+                    format!("{}. {}: {}", i + 1, date.format("%A %Y-%m-%d"), state)
+                })
+                .join("\n")
+        };
+        let content = format!("{}\n\n{}", instructions, list(&response));
+        let channel = match session.user_id().create_dm_channel(ctx).await {
+            Ok(channel) => channel.say(ctx, content).await.map(|_| channel),
+            Err(e) => Err(e),
+        };
+        let channel = match channel {
+            Ok(channel) => channel,
+            Err(e) => {
+                error!("Cannot send DM: {}", e);
+                session
+                    .respond(
+                        Some("Cannot send you a DM; check your privacy settings".to_owned()),
+                        CreateComponents::default(),
+                    )
+                    .await
+                    .expect("Cannot send response");
+                return None;
+            }
+        };
+        session
+            .respond(
+                Some("Check your DMs to respond".to_owned()),
+                CreateComponents::default(),
+            )
+            .await
+            .expect("Cannot send response");
 
         let expiration = Instant::now() + RESP_TIMEOUT;
+        loop {
+            let reply = match channel
+                .id
+                .await_reply(ctx)
+                .author_id(session.user_id())
+                .timeout(expiration.saturating_duration_since(Instant::now()))
+                .await
+            {
+                Some(reply) => reply,
+                None => return Some(EditorOutcome::TimedOut),
+            };
+            let text = reply.content.trim().to_lowercase();
+            tracing::debug!(reply = %text, "text editor reply");
+            match text.as_str() {
+                "done" => {
+                    response.declined = false;
+                    response.flexible = false;
+                    return Some(EditorOutcome::Submit(response));
+                }
+                "cancel" => return Some(EditorOutcome::Cancelled),
+                "delete" if allow_delete => return Some(EditorOutcome::Delete),
+                "all" => response.dates = dates.iter().cloned().collect(),
+                "none" => response.dates.clear(),
+                _ => {
+                    let numbers: Option<Vec<usize>> = text
+                        .split(|c: char| c.is_whitespace() || c == ',')
+                        .filter(|n| !n.is_empty())
+                        .map(|n| {
+                            n.parse::<usize>()
+                                .ok()
+                                .filter(|n| (1..=dates.len()).contains(n))
+                        })
+                        .collect();
+                    match numbers {
+                        Some(numbers) if !numbers.is_empty() => {
+                            for n in numbers {
+                                let date = dates[n - 1];
+                                if !response.dates.remove(&date) {
+                                    response.dates.insert(date);
+                                }
+                            }
+                        }
+                        _ => {
+                            let msg = format!(
+                                "Sorry, I didn't understand that. Reply with date numbers from 1 \
+                                 to {}, {}, or `cancel`.",
+                                dates.len(),
+                                commands
+                            );
+                            if let Err(e) = channel.say(ctx, msg).await {
+                                error!("Cannot send DM: {}", e);
+                            }
+                            continue;
+                        }
+                    }
+                }
+            }
+            if let Err(e) = channel.say(ctx, list(&response)).await {
+                error!("Cannot send DM: {}", e);
+            }
+        }
+    }
 
-        let message = component
-            .get_interaction_response(ctx)
-            .await
-            .expect("Cannot get response message");
+    /// Handles actions in the response editor until it is submitted, deleted, or times out
+    async fn run_editor(
+        &self,
+        host: &impl EditorHost,
+        session: &mut impl ResponseSession,
+        mut editor: Editor,
+        profiles: &Profiles,
+    ) -> EditorOutcome {
+        let expiration = Instant::now() + RESP_TIMEOUT;
         loop {
-            let interaction = message
-                .await_component_interaction(ctx)
-                .timeout(expiration - Instant::now())
-                .await;
-            let interaction = match interaction {
-                Some(i) => i,
+            let action = match session
+                .next_action(expiration.saturating_duration_since(Instant::now()))
+                .await
+            {
+                Some(a) => a,
                 None => {
-                    info!("Response timed out");
-                    component
-                        .edit_original_interaction_response(ctx, |m| {
-                            m.content("Response timed out").components(|c| c)
-                        })
-                        .await
-                        .expect("Cannot update message");
-                    return;
+                    tracing::debug!("editor timed out");
+                    return EditorOutcome::TimedOut;
                 }
             };
-            interaction
-                .defer(ctx)
-                .await
-                .expect("Cannot respond to button");
-            let interaction_id = interaction.data.custom_id.as_str();
-            match interaction_id {
+            let action_id = action.custom_id.as_str();
+            tracing::debug!(action = action_id, values = ?action.values, "editor action");
+            let response = &mut editor.response;
+            match action_id {
                 "submit" => {
-                    if component
-                        .edit_original_interaction_response(ctx, |m| {
-                            m.content("Response submitted").components(|c| c)
-                        })
-                        .await
-                        .is_err()
-                    {
-                        error!("Cannot update message");
-                    }
-                    break;
+                    response.declined = false;
+                    return EditorOutcome::Submit(editor.response);
+                }
+                "decline" => {
+                    return EditorOutcome::Submit(Response {
+                        declined: true,
+                        ..Default::default()
+                    })
+                }
+                "decline_reason" => {
+                    let reason = session
+                        .ask_text(
+                            self.text(Text::DeclineWithReason),
+                            "Why can't you make any of the dates?",
+                            MAX_DECLINE_REASON,
+                            expiration.saturating_duration_since(Instant::now()),
+                        )
+                        .await;
+                    let reason = match reason {
+                        Some(reason) => reason,
+                        None => return EditorOutcome::TimedOut,
+                    };
+                    return EditorOutcome::Submit(Response {
+                        declined: true,
+                        decline_reason: Some(reason).filter(|r| !r.is_empty()),
+                        ..Default::default()
+                    });
                 }
                 "select_all" => {
-                    let blackout_dates = self.blackout_dates.read().unwrap();
-                    response.dates = self
-                        .dates
-                        .iter()
-                        .filter(|d| !blackout_dates.contains(d))
-                        .cloned()
-                        .collect()
+                    let dates = self.get_available_dates();
+                    if dates.len() > SELECT_ALL_CONFIRM {
+                        let mut components = CreateComponents::default();
+                        components.create_action_row(|ar| {
+                            ar.create_button(|b| {
+                                b.style(ButtonStyle::Success)
+                                    .label("Select all")
+                                    .custom_id("confirm_select_all")
+                            })
+                            .create_button(|b| {
+                                b.style(ButtonStyle::Secondary)
+                                    .label("Cancel")
+                                    .custom_id("cancel_select_all")
+                            })
+                        });
+                        let content = Some(format!("Select all {} dates?", dates.len()));
+                        if !Self::edit_editor(session, content, components).await {
+                            return EditorOutcome::TimedOut;
+                        }
+                        continue;
+                    }
+                    response.flexible = false;
+                    response.dates = dates;
                 }
-                "clear_all" => response.dates.clear(),
-                "select" => {
-                    let selections: Vec<usize> = interaction
-                        .data
+                "confirm_select_all" => {
+                    response.flexible = false;
+                    response.dates = self.get_available_dates();
+                }
+                "cancel_select_all" => (),
+                "copy" => {
+                    let profile = profiles.get(session.user_id());
+                    let components = self.create_copy_menu(host, session, profile).await;
+                    let content = Some("Copy whose response?".to_owned());
+                    if !Self::edit_editor(session, content, components).await {
+                        return EditorOutcome::TimedOut;
+                    }
+                    continue;
+                }
+                "copy_from" => match action.values.first().map(String::as_str) {
+                    Some("apply_profile") => {
+                        if let Some(profile) = profiles.get(session.user_id()) {
+                            response.flexible = false;
+                            response.dates = self
+                                .get_available_dates()
+                                .into_iter()
+                                .filter(|d| profile.matches(d))
+                                .collect();
+                        }
+                    }
+                    Some("save_profile") => {
+                        // The rest of the profile is only set by command, so keep it
+                        let profile = Profile {
+                            dates: response.dates.clone(),
+                            ..profiles.get(session.user_id()).unwrap_or_default()
+                        };
+                        if let Err(e) = profiles.set(session.user_id(), profile) {
+                            error!("{}", e);
+                        }
+                    }
+                    // Checked again, since the menu could be replayed after the results were hidden
+                    value if self.can_see_details(session.user_id()) => {
+                        let source = value
+                            .and_then(|v| UserId::from_str(v).ok())
+                            .and_then(|user| self.responses.read().unwrap().get(&user).cloned());
+                        if let Some(source) = source {
+                            response.flexible = source.flexible;
+                            response.dates = source.dates;
+                        }
+                    }
+                    _ => (),
+                },
+                "cancel_copy" => (),
+                "condition" => {
+                    let components = self.create_condition_menu(host, session).await;
+                    let content = Some(
+                        "Only count your response on dates when this user is available:".to_owned(),
+                    );
+                    if !Self::edit_editor(session, content, components).await {
+                        return EditorOutcome::TimedOut;
+                    }
+                    continue;
+                }
+                "condition_user" => {
+                    response.depends_on = action
                         .values
-                        .iter()
-                        .map(|v| v.parse().unwrap())
-                        .collect();
+                        .first()
+                        .and_then(|v| UserId::from_str(v).ok())
+                        .filter(|user| *user != session.user_id())
+                        .filter(|_| self.can_see_details(session.user_id()));
+                }
+                "cancel_condition" => (),
+                "flexible" => {
+                    response.flexible = !response.flexible;
+                    if response.flexible {
+                        response.dates = self.get_available_dates();
+                    }
+                }
+                "clear_all" => {
+                    response.flexible = false;
                     response.dates.clear();
+                }
+                "select" => {
+                    response.flexible = false;
+                    let selections: Vec<usize> =
+                        action.values.iter().map(|v| v.parse().unwrap()).collect();
+                    // Dates hidden by the weekday filter keep their current selection
+                    for (_, date) in self.get_menu_dates(&editor) {
+                        editor.response.dates.remove(&date);
+                    }
                     for index in selections.iter() {
                         let date = &self.dates[*index];
-                        let resp_dates = &mut response.dates;
+                        let resp_dates = &mut editor.response.dates;
                         resp_dates.insert(*date);
                     }
                 }
-                "delete" => {
-                    self.delete_response(ctx, user.id).await;
-                    if component
-                        .edit_original_interaction_response(ctx, |m| {
-                            m.content("Response deleted").components(|c| c)
-                        })
-                        .await
-                        .is_err()
-                    {
-                        error!("Cannot update message");
+                "weekdays" => {
+                    let weekdays: HashSet<Weekday> = action
+                        .values
+                        .iter()
+                        .filter_map(|v| Weekday::from_str(v).ok())
+                        .collect();
+                    let mut filters = self.weekday_filters.write().unwrap();
+                    if weekdays.is_empty() || action.values.iter().any(|v| v == "all") {
+                        filters.remove(&session.user_id());
+                        editor.weekdays = None;
+                    } else {
+                        filters.insert(session.user_id(), weekdays.clone());
+                        editor.weekdays = Some(weekdays);
                     }
-                    return;
+                    drop(filters);
+                    self.save();
+                }
+                "apply_weekday" => {
+                    let weekdays: HashSet<Weekday> = action
+                        .values
+                        .iter()
+                        .filter_map(|v| Weekday::from_str(v).ok())
+                        .collect();
+                    response.flexible = false;
+                    response.dates.extend(
+                        self.get_available_dates()
+                            .into_iter()
+                            .filter(|d| weekdays.contains(&d.weekday())),
+                    );
                 }
-                _ => panic!("Unexpected button: {interaction_id}"),
+                "delete" => return EditorOutcome::Delete,
+                _ => panic!("Unexpected button: {action_id}"),
             }
-            component
-                .edit_original_interaction_response(ctx, |m| {
-                    m.components(|c| self.create_dm_buttons(&response, c, resp_type, allow_delete))
-                })
-                .await
-                .expect("Cannot update message");
+            // Restore the original content after a prompt
+            let content = match action_id {
+                "confirm_select_all" | "cancel_select_all" | "copy_from" | "cancel_copy"
+                | "condition_user" | "cancel_condition" => {
+                    Some(self.get_editor_note(&editor).unwrap_or_default())
+                }
+                _ => None,
+            };
+            let mut components = CreateComponents::default();
+            self.create_dm_buttons(&editor, &mut components);
+            if !Self::edit_editor(session, content, components).await {
+                return EditorOutcome::TimedOut;
+            }
+        }
+    }
+
+    /// Updates the response editor. A failed edit is logged and the editor carries on, since the
+    /// next action redraws it, unless the interaction has expired, in which case this returns
+    /// `false` and the editor should stop.
+    async fn edit_editor(
+        session: &impl ResponseSession,
+        content: Option<String>,
+        components: CreateComponents,
+    ) -> bool {
+        match session.edit(content, components).await {
+            Ok(()) => true,
+            Err(serenity::Error::Http(e))
+                if matches!(&*e, HttpError::UnsuccessfulRequest(r)
+                    if r.error.code == UNKNOWN_WEBHOOK || r.error.code == INVALID_WEBHOOK_TOKEN) =>
+            {
+                info!("Response editor expired");
+                false
+            }
+            Err(e) => {
+                warn!("Cannot update response editor: {}", e);
+                true
+            }
+        }
+    }
+
+    /// Names of other responders, skipping empty responses and users who are no longer in the
+    /// server
+    async fn get_responder_names(
+        &self,
+        host: &impl EditorHost,
+        session: &impl ResponseSession,
+        max: usize,
+    ) -> Vec<(UserId, String)> {
+        // Picking a responder would reveal their dates
+        if !self.can_see_details(session.user_id()) {
+            return vec![];
+        }
+        let candidates = self
+            .responses
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(user_id, r)| **user_id != session.user_id() && !r.dates.is_empty())
+            .map(|(user_id, _)| *user_id)
+            .sorted()
+            .collect_vec();
+        let mut names = vec![];
+        if let Some(guild) = session.guild_id() {
+            for user_id in candidates {
+                if let Some(name) = host.member_name(guild, user_id).await {
+                    names.push((user_id, name));
+                }
+                if names.len() == max {
+                    break;
+                }
+            }
+        }
+        names
+    }
+
+    /// Menu of other responders whose response can be copied, along with the user's own saved
+    /// profile. Responders are only listed if the user can see who is available on each date.
+    async fn create_copy_menu(
+        &self,
+        host: &impl EditorHost,
+        session: &impl ResponseSession,
+        profile: Option<Profile>,
+    ) -> CreateComponents {
+        // Leaves room in the menu for the profile options
+        let names = self
+            .get_responder_names(host, session, MAX_MENU_OPTIONS - 2)
+            .await;
+
+        let mut components = CreateComponents::default();
+        components.create_action_row(|ar| {
+            ar.create_select_menu(|menu| {
+                menu.custom_id("copy_from")
+                    .placeholder("Responder to copy")
+                    .options(|m| {
+                        if profile.is_some_and(|p| p.has_dates()) {
+                            m.create_option(|opt| {
+                                opt.label("My profile")
+                                    .description("Dates you are generally free")
+                                    .value("apply_profile")
+                            });
+                        }
+                        m.create_option(|opt| {
+                            opt.label("Save as my profile")
+                                .description("Use the current selection as your profile")
+                                .value("save_profile")
+                        });
+                        for (user_id, name) in names.iter() {
+                            m.create_option(|opt| opt.label(name).value(user_id));
+                        }
+                        m
+                    })
+            })
+        });
+        components.create_action_row(|ar| {
+            ar.create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label("Cancel")
+                    .custom_id("cancel_copy")
+            })
+        });
+        components
+    }
+
+    /// Menu of other responders that the user's response can be made conditional on, if the user
+    /// can see who is available on each date
+    async fn create_condition_menu(
+        &self,
+        host: &impl EditorHost,
+        session: &impl ResponseSession,
+    ) -> CreateComponents {
+        let names = self
+            .get_responder_names(host, session, MAX_MENU_OPTIONS - 1)
+            .await;
+        let mut components = CreateComponents::default();
+        components.create_action_row(|ar| {
+            ar.create_select_menu(|menu| {
+                menu.custom_id("condition_user")
+                    .placeholder("Responder")
+                    .options(|m| {
+                        m.create_option(|opt| {
+                            opt.label("Anyone")
+                                .description("Count my response regardless of who else comes")
+                                .value("none")
+                        });
+                        for (user_id, name) in names.iter() {
+                            m.create_option(|opt| opt.label(name).value(user_id));
+                        }
+                        m
+                    })
+            })
+        });
+        components.create_action_row(|ar| {
+            ar.create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label("Cancel")
+                    .custom_id("cancel_condition")
+            })
+        });
+        components
+    }
+
+    /// Content shown above the response editor
+    fn get_editor_note(&self, editor: &Editor) -> Option<String> {
+        if editor.resp_type == ResponseType::Confirm {
+            return Some("Select the shortlisted dates you can definitely make".to_owned());
+        }
+        let condition = editor.response.depends_on.map(|user| {
+            format!(
+                "Your response only counts on dates when <@{}> is also available",
+                user
+            )
+        });
+        let preselected = Some("All dates are selected to start with; deselect any you can't make")
+            .filter(|_| editor.preselected)
+            .map(str::to_owned);
+        let notes = [
+            preselected,
+            self.get_blackout_note(editor.resp_type),
+            self.get_blackout_attribution(editor.resp_type),
+            condition,
+        ]
+        .into_iter()
+        .flatten()
+        .collect_vec();
+        Some(notes.join("\n")).filter(|_| !notes.is_empty())
+    }
+
+    fn get_available_dates(&self) -> HashSet<NaiveDate> {
+        let blackout_dates = self.blackout_dates.read().unwrap();
+        self.dates
+            .iter()
+            .filter(|d| !blackout_dates.contains_key(d))
+            .cloned()
+            .collect()
+    }
+
+    /// Select menu options can't be individually disabled, so blackout dates are listed
+    /// separately when they are not hidden entirely
+    fn get_blackout_note(&self, resp_type: ResponseType) -> Option<String> {
+        if !self.show_blackout || resp_type != ResponseType::Normal {
+            return None;
+        }
+        let blackout_dates = self.blackout_dates.read().unwrap();
+        if blackout_dates.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Not available: {}",
+            self.dates
+                .iter()
+                .filter(|d| blackout_dates.contains_key(d))
+                .map(|d| d.format("~~%a %b %d~~"))
+                .join(", ")
+        ))
+    }
+
+    /// Who excluded each blackout date, shown in the blackout editor. Only the owner can open
+    /// it, so this isn't shown to anyone else.
+    fn get_blackout_attribution(&self, resp_type: ResponseType) -> Option<String> {
+        if resp_type != ResponseType::Blackout {
+            return None;
         }
+        let blackout_dates = self.blackout_dates.read().unwrap();
+        let lines = self
+            .dates
+            .iter()
+            .filter_map(|date| Some((*blackout_dates.get(date)?, date)))
+            .into_group_map()
+            .into_iter()
+            .sorted_by_key(|(_, dates)| dates[0])
+            .map(|(user, dates)| {
+                let excluded_by = match user {
+                    Some(user) => format!("<@{}>", user),
+                    None => "unknown".to_owned(),
+                };
+                format!(
+                    "Excluded by {}: {}",
+                    excluded_by,
+                    dates.iter().map(|d| d.format("%a %b %d")).join(", ")
+                )
+            })
+            .collect_vec();
+        Some(lines.join("\n")).filter(|_| !lines.is_empty())
+    }
 
-        match resp_type {
-            ResponseType::Normal => self.add_response(ctx, user.id, response).await,
-            ResponseType::Blackout => self.set_blackout(ctx, response).await,
+    /// Dates shown in the editor's date menu, with their index in `dates`
+    fn get_menu_dates(&self, editor: &Editor) -> Vec<(usize, NaiveDate)> {
+        let blackout_dates = self.blackout_dates.read().unwrap();
+        let shortlist = self.get_shortlist();
+        let available = self
+            .dates
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, date)| match editor.resp_type {
+                ResponseType::Blackout => true,
+                ResponseType::Confirm => shortlist.as_ref().is_some_and(|s| s.contains(date)),
+                _ => !blackout_dates.contains_key(date),
+            })
+            .collect_vec();
+        let filtered = match &editor.weekdays {
+            Some(weekdays) => available
+                .iter()
+                .filter(|(_, date)| weekdays.contains(&date.weekday()))
+                .cloned()
+                .collect_vec(),
+            None => return available,
         };
+        // Fall back to all dates rather than showing an empty menu
+        if filtered.is_empty() {
+            available
+        } else {
+            filtered
+        }
     }
 
     fn create_dm_buttons<'a>(
         &self,
-        response: &Response,
+        editor: &Editor,
         components: &'a mut CreateComponents,
-        resp_type: ResponseType,
-        allow_delete: bool,
     ) -> &'a mut CreateComponents {
+        let response = &editor.response;
+        let resp_type = editor.resp_type;
         let mut ar = CreateActionRow::default();
         let mut menu = CreateSelectMenu::default();
         let mut count = 0;
         menu.options(|m| {
-            for (i, date) in self.dates.iter().enumerate() {
-                if resp_type == ResponseType::Normal
-                    && self.blackout_dates.read().unwrap().contains(date)
-                {
-                    continue;
-                }
+            for (i, date) in self.get_menu_dates(editor) {
                 m.create_option(|opt| {
                     count += 1;
                     opt.label(date.format("%a %b %d"));
                     opt.value(format!("{}", i));
-                    opt.default_selection(response.dates.contains(date));
+                    opt.default_selection(response.dates.contains(&date));
                     opt
                 });
             }
@@ -450,36 +4860,140 @@ impl Scheduler {
         ar.add_select_menu(menu);
         components.add_action_row(ar);
 
+        let weekdays = self
+            .dates
+            .iter()
+            .map(|d| d.weekday())
+            .unique()
+            .collect_vec();
+        // The shortlist is short enough not to need filtering or bulk selection
+        let bulk = !matches!(resp_type, ResponseType::Blackout | ResponseType::Confirm);
+        if bulk && weekdays.len() > 1 {
+            ar = CreateActionRow::default();
+            let mut menu = CreateSelectMenu::default();
+            menu.options(|m| {
+                m.create_option(|opt| opt.label(self.text(Text::AllWeekdays)).value("all"));
+                for day in weekdays.iter() {
+                    m.create_option(|opt| {
+                        opt.label(weekday_name(*day, self.locale.as_deref()))
+                            .value(day.to_string())
+                            .default_selection(
+                                editor.weekdays.as_ref().is_some_and(|w| w.contains(day)),
+                            )
+                    });
+                }
+                m
+            });
+            menu.custom_id("weekdays");
+            menu.placeholder(self.text(Text::FilterWeekdays));
+            menu.min_values(0);
+            menu.max_values(weekdays.len() as u64 + 1);
+            ar.add_select_menu(menu);
+            components.add_action_row(ar);
+
+            ar = CreateActionRow::default();
+            let mut menu = CreateSelectMenu::default();
+            menu.options(|m| {
+                for day in weekdays.iter() {
+                    m.create_option(|opt| {
+                        opt.label(format!(
+                            "{} {}",
+                            self.text(Text::AvailableEvery),
+                            weekday_name(*day, self.locale.as_deref())
+                        ))
+                        .value(day.to_string())
+                    });
+                }
+                m
+            });
+            menu.custom_id("apply_weekday");
+            menu.placeholder(self.text(Text::ApplyWeekday));
+            ar.add_select_menu(menu);
+            components.add_action_row(ar);
+        }
+
         ar = CreateActionRow::default();
 
-        if resp_type != ResponseType::Blackout {
+        if bulk {
             let mut button = CreateButton::default();
-            button.label("Select all");
+            button.label(self.text(Text::SelectAll));
             button.custom_id("select_all");
             button.style(ButtonStyle::Success);
             ar.add_button(button);
 
             let mut button = CreateButton::default();
-            button.label("Clear all");
+            button.label(self.text(Text::ClearAll));
             button.custom_id("clear_all");
             button.style(ButtonStyle::Secondary);
             ar.add_button(button);
         }
 
+        if resp_type == ResponseType::Normal {
+            let mut button = CreateButton::default();
+            button.label(self.text(Text::Flexible));
+            button.custom_id("flexible");
+            button.style(if response.flexible {
+                ButtonStyle::Success
+            } else {
+                ButtonStyle::Secondary
+            });
+            ar.add_button(button);
+        }
+
+        if resp_type == ResponseType::Normal {
+            let mut button = CreateButton::default();
+            button.label(self.text(Text::CopyResponse));
+            button.custom_id("copy");
+            button.style(ButtonStyle::Secondary);
+            ar.add_button(button);
+        }
+
         let mut button = CreateButton::default();
-        button.label("Submit");
+        button.label(self.text(Text::Submit));
         button.custom_id("submit");
         ar.add_button(button);
 
         components.add_action_row(ar);
 
-        if allow_delete {
+        if resp_type == ResponseType::Normal || editor.allow_delete {
             ar = CreateActionRow::default();
-            let mut button = CreateButton::default();
-            button.label("Delete response");
-            button.custom_id("delete");
-            button.style(ButtonStyle::Danger);
-            ar.add_button(button);
+            if resp_type == ResponseType::Normal {
+                let mut button = CreateButton::default();
+                button.label(self.text(Text::Decline));
+                button.custom_id("decline");
+                button.style(if response.declined {
+                    ButtonStyle::Primary
+                } else {
+                    ButtonStyle::Secondary
+                });
+                ar.add_button(button);
+
+                let mut button = CreateButton::default();
+                button.label(self.text(Text::DeclineWithReason));
+                button.custom_id("decline_reason");
+                button.style(ButtonStyle::Secondary);
+                ar.add_button(button);
+
+                // Kept while a condition is set, so that it can be removed
+                if editor.show_responders || response.depends_on.is_some() {
+                    let mut button = CreateButton::default();
+                    button.label(self.text(Text::Condition));
+                    button.custom_id("condition");
+                    button.style(if response.depends_on.is_some() {
+                        ButtonStyle::Primary
+                    } else {
+                        ButtonStyle::Secondary
+                    });
+                    ar.add_button(button);
+                }
+            }
+            if editor.allow_delete {
+                let mut button = CreateButton::default();
+                button.label(self.text(Text::DeleteResponse));
+                button.custom_id("delete");
+                button.style(ButtonStyle::Danger);
+                ar.add_button(button);
+            }
             components.add_action_row(ar);
         }
 
@@ -495,8 +5009,15 @@ impl Scheduler {
             let mut repost = self.repost_message.write().unwrap();
             *repost = message.as_ref().map(|m| m.into());
         }
+        self.messages_since_post.store(0, Ordering::Relaxed);
         self.save();
-        if message.is_some() {
+        if let Some(message) = message {
+            self.refresh_channel_names(ctx).await;
+            info!(
+                "scheduler {} reposted in {}",
+                self.get_id(),
+                self.get_channel_name(message.channel_id)
+            );
             self.update_messages(ctx).await;
         }
     }
@@ -510,4 +5031,791 @@ impl Scheduler {
             }
         }
     }
+
+    /// Whether the original scheduler message can still be found
+    pub async fn message_exists(&self, ctx: &Context) -> serenity::Result<bool> {
+        let message = self.get_message();
+        match message.channel_id.message(ctx, message.message_id).await {
+            Ok(_) => Ok(true),
+            Err(serenity::Error::Http(e))
+                if matches!(&*e, HttpError::UnsuccessfulRequest(r)
+                    if r.error.code == UNKNOWN_MESSAGE || r.error.code == UNKNOWN_CHANNEL) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replaces the original scheduler message with `message`, keeping all responses. This
+    /// changes the scheduler's id, so the caller is responsible for updating the registry.
+    /// Returns the old message.
+    pub async fn recover(&self, ctx: &Context, message: Message) -> MessageShim {
+        let old_message = std::mem::replace(&mut *self.message.write().unwrap(), message.into());
+        let old_id = old_message.message_id;
+        // The new message hasn't been pinned
+        self.pinned.store(false, Ordering::Relaxed);
+        // Only forget the old file once the scheduler is saved under its new id
+        match self.try_save() {
+            Ok(()) => crate::delete_file(&old_id),
+            Err(e) => error!("{}", e),
+        }
+        info!("scheduler {} recovered as {}", old_id, self.get_id());
+        self.update_messages(ctx).await;
+        old_message
+    }
+
+    /// Prepares a scheduler exported from another bot instance to be posted as `message` by
+    /// `owner`. Everything that refers to the other instance's messages, channels, or events is
+    /// dropped, along with the group if the role isn't in `guild`.
+    pub fn rebind(
+        &mut self,
+        ctx: &Context,
+        owner: UserId,
+        guild: Option<GuildId>,
+        message: Message,
+    ) {
+        *self.owner.get_mut().unwrap() = owner;
+        *self.message.get_mut().unwrap() = message.into();
+        *self.repost_message.get_mut().unwrap() = None;
+        self.threads.get_mut().unwrap().clear();
+        self.channel_names.get_mut().unwrap().clear();
+        *self.event.get_mut().unwrap() = None;
+        *self.pinned.get_mut() = false;
+        self.group = self
+            .group
+            .filter(|role| guild.is_some_and(|guild| ctx.cache.role(guild, *role).is_some()));
+    }
+
+    /// Deletes the scheduler's messages and persisted data. The caller is responsible for
+    /// removing it from the in-memory maps first, so the resulting message delete events are
+    /// ignored.
+    pub async fn destroy(&self, ctx: &Context) {
+        info!("destroying scheduler: {}", self.get_id());
+        self.destroyed.store(true, Ordering::Relaxed);
+        self.delete_repost(ctx).await;
+        // Deleting the message also removes it from the channel's pins
+        if let Err(e) = self.get_message().delete(ctx).await {
+            error!("can't delete scheduler message: {e}");
+        }
+        crate::delete_file(&self.get_id());
+    }
+}
+
+/// Parses a time of day such as `18:00`, `6pm` or `6:30pm`
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    let s = s.trim().to_lowercase();
+    let (s, pm) = match (s.strip_suffix("am"), s.strip_suffix("pm")) {
+        (Some(s), _) => (s.trim(), Some(false)),
+        (_, Some(s)) => (s.trim(), Some(true)),
+        _ => (s.as_str(), None),
+    };
+    let (hour, minute) = s.split_once(':').unwrap_or((s, "0"));
+    let (mut hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+    if let Some(pm) = pm {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Reads the blackout dates, which were saved as a list of dates before who excluded each one
+/// was recorded
+fn deserialize_blackout_dates<'de, D>(
+    deserializer: D,
+) -> Result<RwLock<HashMap<NaiveDate, Option<UserId>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BlackoutDates {
+        Attributed(HashMap<NaiveDate, Option<UserId>>),
+        Unattributed(HashSet<NaiveDate>),
+    }
+    let dates = match BlackoutDates::deserialize(deserializer)? {
+        BlackoutDates::Attributed(dates) => dates,
+        BlackoutDates::Unattributed(dates) => dates.into_iter().map(|d| (d, None)).collect(),
+    };
+    Ok(dates.into())
+}
+
+/// Joins lines into as few messages as fit in Discord's length limit, wrapping any line too long
+/// to fit
+fn split_messages(lines: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut messages = vec![];
+    let mut content = String::new();
+    for line in lines.into_iter().flat_map(wrap_line) {
+        if content.len() + line.len() >= 2000 {
+            messages.push(content);
+            content = String::new()
+        }
+        content += &line;
+        content.push('\n');
+    }
+    messages.push(content);
+    messages
+}
+
+/// Break a line too long for one message at its ", " separators, e.g. a date with a long list of
+/// responders
+fn wrap_line(line: String) -> Vec<String> {
+    if line.len() < 2000 {
+        return vec![line];
+    }
+    let mut lines = vec![];
+    let mut current = String::new();
+    for part in line.split_inclusive(", ") {
+        if !current.is_empty() && current.len() + part.len() >= 2000 {
+            lines.push(current);
+            current = String::new();
+        }
+        current += part;
+    }
+    lines.push(current);
+    lines
+}
+
+fn is_thread(kind: ChannelType) -> bool {
+    matches!(
+        kind,
+        ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread
+    )
+}
+
+/// Channel to create discussion threads in for a scheduler in `channel` of type `kind`, given its
+/// parent and the parent's type if that could be looked up
+fn choose_thread_channel(
+    channel: ChannelId,
+    kind: ChannelType,
+    parent: Option<(ChannelId, Option<ChannelType>)>,
+) -> Result<ChannelId, &'static str> {
+    match parent {
+        Some((parent, parent_kind)) if is_thread(kind) => match parent_kind {
+            // Forum channels aren't known to this version of serenity, so check for the channel
+            // types that threads can be created in instead
+            Some(ChannelType::Text | ChannelType::News) | None => Ok(parent),
+            Some(_) => Err("Threads can't be created for a scheduler in a forum post"),
+        },
+        _ => Ok(channel),
+    }
+}
+
+/// Escapes text entered by a user so that it is shown as typed rather than as markdown
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '#' | '[' | ']'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{EditorAction, ScriptedSession};
+    use proptest::prelude::*;
+    use serenity::async_trait;
+
+    /// Runs the editor without a connection to Discord
+    struct OfflineHost;
+
+    #[async_trait]
+    impl EditorHost for OfflineHost {
+        async fn member_name(&self, _guild: GuildId, _user: UserId) -> Option<String> {
+            None
+        }
+
+        async fn update_messages(&self, _scheduler: &Scheduler) {}
+
+        fn context(&self) -> Option<&Context> {
+            None
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn scheduler(dates: &[NaiveDate]) -> Scheduler {
+        serde_json::from_value(serde_json::json!({
+            "owner": "1",
+            "title": "Test",
+            "dates": dates,
+            "group": null,
+            "message": { "message_id": "2", "channel_id": "3" },
+            "responses": {},
+            "closed": false,
+        }))
+        .unwrap()
+    }
+
+    fn action(custom_id: &str, values: &[&str]) -> EditorAction {
+        EditorAction {
+            custom_id: custom_id.to_owned(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    // Schedulers are saved to the data directory under the working directory
+    fn use_temp_dir() {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("scheduler-test-{}", std::process::id()));
+            std::fs::create_dir_all(dir.join(crate::DATA_DIR)).unwrap();
+            std::env::set_current_dir(dir).unwrap();
+        });
+    }
+
+    fn weekend() -> HashSet<Weekday> {
+        HashSet::from([Weekday::Sat, Weekday::Sun])
+    }
+
+    #[test]
+    fn no_weekdays_is_rejected() {
+        let result =
+            Scheduler::generate_dates(10, None, None, &HashSet::new(), None, date(2024, 1, 1));
+        assert!(matches!(result, Err(SchedulerError::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn limit_below_weekday_count_is_rejected() {
+        let result = Scheduler::generate_dates(1, None, None, &weekend(), None, date(2024, 1, 1));
+        assert!(matches!(result, Err(SchedulerError::InvalidOptions(_))));
+    }
+
+    fn assert_invalid(result: Result<Vec<NaiveDate>, SchedulerError>) {
+        assert!(matches!(result, Err(SchedulerError::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn negative_limit_is_rejected() {
+        let today = date(2024, 1, 1);
+        assert_invalid(Scheduler::generate_dates(
+            -4,
+            None,
+            None,
+            &weekend(),
+            None,
+            today,
+        ));
+    }
+
+    #[test]
+    fn negative_skip_is_rejected() {
+        let today = date(2024, 1, 1);
+        assert_invalid(Scheduler::generate_dates(
+            4,
+            Some(-1),
+            None,
+            &weekend(),
+            None,
+            today,
+        ));
+    }
+
+    #[test]
+    fn skip_beyond_maximum_is_rejected() {
+        let today = date(2024, 1, 1);
+        let skip = Some(MAX_SKIP_WEEKS + 1);
+        assert_invalid(Scheduler::generate_dates(
+            4,
+            skip,
+            None,
+            &weekend(),
+            None,
+            today,
+        ));
+    }
+
+    #[test]
+    fn start_date_near_maximum_is_rejected() {
+        let today = date(2024, 1, 1);
+        let start = NaiveDate::MAX - Duration::days(3);
+        assert_invalid(Scheduler::generate_dates(
+            4,
+            None,
+            Some(start),
+            &weekend(),
+            None,
+            today,
+        ));
+        let today = NaiveDate::MAX - Duration::days(3);
+        assert_invalid(Scheduler::generate_dates(
+            4,
+            None,
+            None,
+            &weekend(),
+            None,
+            today,
+        ));
+    }
+
+    /// Checks that `dates` are strictly increasing and only fall on `days`
+    fn assert_ordered_on(dates: &[NaiveDate], days: &HashSet<Weekday>) {
+        assert!(dates.windows(2).all(|w| w[0] < w[1]), "{:?}", dates);
+        assert!(
+            dates.iter().all(|d| days.contains(&d.weekday())),
+            "{:?}",
+            dates
+        );
+    }
+
+    #[test]
+    fn dates_across_leap_day() {
+        let days = HashSet::from([Weekday::Mon, Weekday::Thu, Weekday::Sat]);
+        let start = Some(date(2024, 2, 26));
+        let dates = Scheduler::generate_dates(9, None, start, &days, None, date(2024, 1, 1));
+        let dates = dates.unwrap();
+        assert_eq!(dates.len(), 9);
+        assert!(dates.contains(&date(2024, 2, 29)));
+        assert_ordered_on(&dates, &days);
+    }
+
+    #[test]
+    fn dates_across_year_end() {
+        let days = HashSet::from([Weekday::Mon, Weekday::Wed, Weekday::Sun]);
+        let start = Some(date(2024, 12, 30));
+        let dates = Scheduler::generate_dates(9, None, start, &days, None, date(2024, 1, 1));
+        let dates = dates.unwrap();
+        assert_eq!(dates.len(), 9);
+        assert_eq!(dates[0], date(2024, 12, 30));
+        assert!(dates.contains(&date(2025, 1, 1)));
+        assert_ordered_on(&dates, &days);
+    }
+
+    #[test]
+    fn biweekly_dates_skip_alternate_weeks() {
+        let today = date(2024, 1, 1);
+        let start = Some(date(2024, 1, 6));
+        let weekly = Scheduler::generate_dates(6, None, start, &weekend(), None, today).unwrap();
+        let biweekly =
+            Scheduler::generate_dates(6, None, start, &weekend(), Some(2), today).unwrap();
+        let weekly_expected = [
+            date(2024, 1, 6),
+            date(2024, 1, 7),
+            date(2024, 1, 13),
+            date(2024, 1, 14),
+            date(2024, 1, 20),
+            date(2024, 1, 21),
+        ];
+        let biweekly_expected = [
+            date(2024, 1, 6),
+            date(2024, 1, 7),
+            date(2024, 1, 20),
+            date(2024, 1, 21),
+            date(2024, 2, 3),
+            date(2024, 2, 4),
+        ];
+        assert_eq!(weekly, weekly_expected);
+        assert_eq!(biweekly, biweekly_expected);
+    }
+
+    #[test]
+    fn interval_out_of_range_is_rejected() {
+        let today = date(2024, 1, 1);
+        for interval in [0, MAX_INTERVAL_WEEKS + 1] {
+            let result =
+                Scheduler::generate_dates(4, None, None, &weekend(), Some(interval), today);
+            assert_invalid(result);
+        }
+    }
+
+    #[test]
+    fn many_responders_fit_in_field() {
+        let dates = [date(2024, 1, 6)];
+        let scheduler = scheduler(&dates);
+        {
+            let mut responses = scheduler.responses.write().unwrap();
+            for i in 0..200 {
+                let user = UserId(100_000_000_000_000_000 + i);
+                responses.insert(user, HashSet::from(dates).into());
+            }
+        }
+        let (field, collapsed) = scheduler.get_responses();
+        assert!(collapsed);
+        assert!(field.chars().count() <= FIELD_LIMIT);
+        let (list, _) = field.split_once(')').unwrap();
+        let shown = list.matches("<@").count();
+        assert!(list.ends_with(&format!("+{} more", 200 - shown)));
+    }
+
+    #[test]
+    fn shortlist_follows_tie_break() {
+        use_temp_dir();
+        let dates = [date(2024, 1, 5), date(2024, 1, 6), date(2024, 1, 7)];
+        let scheduler = scheduler(&dates);
+        scheduler
+            .responses
+            .write()
+            .unwrap()
+            .insert(UserId(100), HashSet::from(dates).into());
+        scheduler
+            .set_tie_break(UserId(1), TieBreak::Weekday(Weekday::Sat))
+            .unwrap();
+        scheduler.set_shortlist(UserId(1), 1).unwrap();
+        assert_eq!(scheduler.get_shortlist(), Some(vec![date(2024, 1, 6)]));
+        assert_eq!(scheduler.get_leading_field().unwrap(), "Sat Jan 6 (1)");
+    }
+
+    /// Scheduler with pseudo-random responses, including flexible and conditional ones, blackout
+    /// dates, and role blocks
+    fn large_scheduler(num_dates: usize, num_responders: u64) -> Scheduler {
+        let dates = (0..num_dates as i64)
+            .map(|i| date(2024, 1, 1) + Duration::days(i))
+            .collect_vec();
+        let scheduler = scheduler(&dates);
+        let mut seed: u64 = 1;
+        let mut next = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed >> 33
+        };
+        {
+            let mut responses = scheduler.responses.write().unwrap();
+            for user in 0..num_responders {
+                let mut response: Response = dates
+                    .iter()
+                    .filter(|_| next() % 3 == 0)
+                    .cloned()
+                    .collect::<HashSet<_>>()
+                    .into();
+                response.flexible = next() % 20 == 0;
+                if next() % 10 == 0 {
+                    response.depends_on = Some(UserId(next() % (num_responders + 5)));
+                }
+                responses.insert(UserId(user), response);
+            }
+            let mut blackout_dates = scheduler.blackout_dates.write().unwrap();
+            for date in dates.iter().filter(|_| next() % 10 == 0) {
+                blackout_dates.insert(*date, None);
+            }
+            let mut role_blocks = scheduler.role_blocks.write().unwrap();
+            for role in 0..3 {
+                let block = RoleBlock {
+                    dates: dates.iter().filter(|_| next() % 2 == 0).cloned().collect(),
+                    size: role as usize + 2,
+                };
+                role_blocks.insert(RoleId(role), block);
+            }
+        }
+        scheduler
+    }
+
+    /// The tally built the slow way, checking every response for every date
+    fn tally_per_date(scheduler: &Scheduler) -> Vec<DateTally> {
+        let responses = scheduler.responses.read().unwrap();
+        let blackout_dates = scheduler.blackout_dates.read().unwrap();
+        let role_blocks = scheduler.role_blocks.read().unwrap();
+        let satisfied = |response: &Response, date: &NaiveDate| match response.depends_on {
+            Some(dependency) => responses
+                .get(&dependency)
+                .is_some_and(|d| d.is_available(date)),
+            None => true,
+        };
+        scheduler
+            .dates
+            .iter()
+            .filter(|date| !blackout_dates.contains_key(date))
+            .map(|date| DateTally {
+                date: *date,
+                users: responses
+                    .iter()
+                    .filter(|(_, r)| !r.flexible && r.dates.contains(date) && satisfied(r, date))
+                    .map(|(user_id, _)| *user_id)
+                    .collect(),
+                flexible: responses
+                    .iter()
+                    .filter(|(_, r)| r.flexible && satisfied(r, date))
+                    .map(|(user_id, _)| *user_id)
+                    .collect(),
+                blocks: role_blocks
+                    .iter()
+                    .filter(|(_, block)| block.dates.contains(date))
+                    .map(|(role_id, block)| (*role_id, block.size))
+                    .sorted()
+                    .collect(),
+                conditional: responses
+                    .iter()
+                    .filter(|(_, r)| !r.flexible && r.dates.contains(date) && satisfied(r, date))
+                    .filter_map(|(user_id, r)| r.depends_on.map(|d| (*user_id, d)))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tally_matches_per_date_tally() {
+        let scheduler = large_scheduler(60, 80);
+        let tally = scheduler.get_tally();
+        let expected = tally_per_date(&scheduler);
+        assert_eq!(tally.len(), expected.len());
+        for (tally, expected) in tally.iter().zip(expected.iter()) {
+            assert_eq!(tally.date, expected.date);
+            assert_eq!(tally.users, expected.users);
+            assert_eq!(tally.flexible, expected.flexible);
+            assert_eq!(tally.blocks, expected.blocks);
+            assert_eq!(tally.conditional, expected.conditional);
+        }
+    }
+
+    // Timing depends on the machine and build, so run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn tally_timing() {
+        let scheduler = large_scheduler(500, 500);
+        let runs = 20;
+        let start = Instant::now();
+        for _ in 0..runs {
+            std::hint::black_box(scheduler.get_tally());
+        }
+        let single_pass = start.elapsed() / runs;
+        let start = Instant::now();
+        for _ in 0..runs {
+            std::hint::black_box(tally_per_date(&scheduler));
+        }
+        let per_date = start.elapsed() / runs;
+        assert!(
+            single_pass < per_date,
+            "single pass: {:?}, per date: {:?}",
+            single_pass,
+            per_date
+        );
+    }
+
+    #[test]
+    fn threads_for_a_channel_go_in_the_channel() {
+        let channel = ChannelId(1);
+        let result = choose_thread_channel(channel, ChannelType::Text, None);
+        assert_eq!(result, Ok(channel));
+        let result = choose_thread_channel(channel, ChannelType::News, None);
+        assert_eq!(result, Ok(channel));
+    }
+
+    #[test]
+    fn threads_for_a_thread_go_in_its_parent() {
+        let (thread, parent) = (ChannelId(1), ChannelId(2));
+        for kind in [ChannelType::PublicThread, ChannelType::PrivateThread] {
+            let result =
+                choose_thread_channel(thread, kind, Some((parent, Some(ChannelType::Text))));
+            assert_eq!(result, Ok(parent));
+        }
+        let parent_kind = Some(ChannelType::News);
+        let result =
+            choose_thread_channel(thread, ChannelType::NewsThread, Some((parent, parent_kind)));
+        assert_eq!(result, Ok(parent));
+        // The parent is still tried if it can't be looked up
+        let result = choose_thread_channel(thread, ChannelType::PublicThread, Some((parent, None)));
+        assert_eq!(result, Ok(parent));
+        let result = choose_thread_channel(thread, ChannelType::PublicThread, None);
+        assert_eq!(result, Ok(thread));
+    }
+
+    #[test]
+    fn threads_for_a_forum_post_are_refused() {
+        let parent = Some((ChannelId(2), Some(ChannelType::Unknown)));
+        let result = choose_thread_channel(ChannelId(1), ChannelType::PublicThread, parent);
+        assert!(result.is_err());
+    }
+
+    /// Dates, whether each is blacked out, and each response's flexibility and selected dates
+    type Poll = (Vec<NaiveDate>, Vec<bool>, Vec<(bool, Vec<bool>)>);
+
+    fn poll() -> impl Strategy<Value = Poll> {
+        (1usize..40).prop_flat_map(|n| {
+            let dates = proptest::collection::btree_set(0i64..400, n).prop_map(|offsets| {
+                offsets
+                    .into_iter()
+                    .map(|i| date(2024, 1, 1) + Duration::days(i))
+                    .collect_vec()
+            });
+            let blackout = proptest::collection::vec(proptest::bool::weighted(0.2), n);
+            let response = (
+                proptest::bool::weighted(0.1),
+                proptest::collection::vec(any::<bool>(), n),
+            );
+            let responses = proptest::collection::vec(response, 0..150);
+            (dates, blackout, responses)
+        })
+    }
+
+    fn poll_scheduler((dates, blackout, responses): &Poll) -> Scheduler {
+        let scheduler = scheduler(dates);
+        {
+            let mut blackout_dates = scheduler.blackout_dates.write().unwrap();
+            for (date, _) in dates.iter().zip(blackout).filter(|(_, b)| **b) {
+                blackout_dates.insert(*date, None);
+            }
+            let mut map = scheduler.responses.write().unwrap();
+            for (i, (flexible, selected)) in responses.iter().enumerate() {
+                let mut response: Response = dates
+                    .iter()
+                    .zip(selected)
+                    .filter(|(_, s)| **s)
+                    .map(|(d, _)| *d)
+                    .collect::<HashSet<_>>()
+                    .into();
+                response.flexible = *flexible;
+                map.insert(UserId(100_000_000_000_000_000 + i as u64), response);
+            }
+        }
+        scheduler
+    }
+
+    proptest! {
+        #[test]
+        fn tally_has_each_date_once(poll in poll()) {
+            let scheduler = poll_scheduler(&poll);
+            let (dates, blackout, _) = &poll;
+            let expected = dates
+                .iter()
+                .zip(blackout)
+                .filter(|(_, b)| !**b)
+                .map(|(d, _)| *d)
+                .collect_vec();
+            let tally = scheduler.get_tally().iter().map(|t| t.date).collect_vec();
+            prop_assert_eq!(tally, expected);
+        }
+
+        #[test]
+        fn counts_never_exceed_responders(poll in poll()) {
+            let scheduler = poll_scheduler(&poll);
+            let responders = poll.2.len();
+            for tally in scheduler.get_tally() {
+                prop_assert!(tally.count() <= responders);
+            }
+        }
+
+        #[test]
+        fn underlined_dates_have_most_responses(poll in poll()) {
+            let scheduler = poll_scheduler(&poll);
+            let tally = scheduler.get_tally();
+            let max = Scheduler::get_max_count(&tally);
+            let expected: HashSet<String> = tally
+                .iter()
+                .filter(|t| max > 0 && t.count() == max)
+                .map(|t| t.date.format("%a %Y-%m-%d").to_string())
+                .collect();
+            let underlined: HashSet<String> = scheduler
+                .get_results(false, false)
+                .filter_map(|line| {
+                    let date = line.strip_prefix("__`")?.split_once(":`")?.0;
+                    Some(date.to_owned())
+                })
+                .collect();
+            prop_assert_eq!(underlined, expected);
+        }
+
+        #[test]
+        fn detail_messages_fit_limit(poll in poll()) {
+            let scheduler = poll_scheduler(&poll);
+            for message in split_messages(scheduler.get_results(true, true)) {
+                prop_assert!(message.chars().count() <= 2000);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn select_and_submit_records_dates() {
+        use_temp_dir();
+        let dates = [date(2024, 1, 6), date(2024, 1, 7), date(2024, 1, 13)];
+        let scheduler = scheduler(&dates);
+        let user = UserId(10);
+        let actions = vec![action("select", &["0", "2"]), action("submit", &[])];
+        let mut session = ScriptedSession::new(user, None, actions);
+        scheduler
+            .get_response(
+                &OfflineHost,
+                &mut session,
+                ResponseType::Normal,
+                &Profiles::default(),
+            )
+            .await;
+        let transcript = session.into_transcript();
+        assert_eq!(
+            transcript.last().map(String::as_str),
+            Some("edit: Response submitted")
+        );
+        let responses = scheduler.responses.read().unwrap();
+        assert_eq!(responses[&user].dates, HashSet::from([dates[0], dates[2]]));
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn times_parse_in_either_clock() {
+        assert_eq!(parse_time("18:00"), Some(time(18, 0)));
+        assert_eq!(parse_time("6pm"), Some(time(18, 0)));
+        assert_eq!(parse_time(" 6:30 PM "), Some(time(18, 30)));
+        assert_eq!(parse_time("9am"), Some(time(9, 0)));
+    }
+
+    #[test]
+    fn twelve_am_and_pm_are_midnight_and_noon() {
+        assert_eq!(parse_time("12am"), Some(time(0, 0)));
+        assert_eq!(parse_time("12:30am"), Some(time(0, 30)));
+        assert_eq!(parse_time("12pm"), Some(time(12, 0)));
+        assert_eq!(parse_time("12:30pm"), Some(time(12, 30)));
+    }
+
+    #[test]
+    fn invalid_times_are_rejected() {
+        for s in ["0am", "13pm", "24:00", "6:60", "six", ""] {
+            assert_eq!(parse_time(s), None, "{}", s);
+        }
+    }
+
+    /// Best windows on a single date where each responder gave one of `ranges`
+    fn time_windows(ranges: &[(NaiveTime, NaiveTime)]) -> Vec<String> {
+        let dates = [date(2024, 1, 6)];
+        let scheduler = scheduler(&dates);
+        scheduler.time_ranges.store(true, Ordering::Relaxed);
+        {
+            let mut responses = scheduler.responses.write().unwrap();
+            for (i, range) in ranges.iter().enumerate() {
+                let mut response: Response = HashSet::from(dates).into();
+                response.times.insert(dates[0], *range);
+                responses.insert(UserId(100 + i as u64), response);
+            }
+        }
+        scheduler.get_time_windows()
+    }
+
+    #[test]
+    fn overlapping_ranges_give_their_overlap() {
+        let windows = time_windows(&[(time(18, 0), time(21, 0)), (time(19, 0), time(22, 0))]);
+        assert_eq!(windows, ["Sat Jan 06: 7:00pm–9:00pm works for 2 people"]);
+    }
+
+    #[test]
+    fn disjoint_ranges_give_the_longest() {
+        let windows = time_windows(&[(time(13, 0), time(15, 0)), (time(18, 0), time(19, 0))]);
+        assert_eq!(windows, ["Sat Jan 06: 1:00pm–3:00pm works for 1 person"]);
+    }
+
+    #[test]
+    fn nested_ranges_give_the_inner_one() {
+        let windows = time_windows(&[
+            (time(17, 0), time(23, 0)),
+            (time(19, 0), time(20, 0)),
+            (time(18, 0), time(22, 0)),
+        ]);
+        assert_eq!(windows, ["Sat Jan 06: 7:00pm–8:00pm works for 3 people"]);
+    }
+
+    #[test]
+    fn dates_without_times_have_no_window() {
+        assert!(time_windows(&[]).is_empty());
+    }
 }
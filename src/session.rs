@@ -0,0 +1,376 @@
+use crate::scheduler::Scheduler;
+
+use log::{error, warn};
+use serenity::async_trait;
+use serenity::builder::CreateComponents;
+use serenity::client::Context;
+use serenity::model::application::component::{ActionRowComponent, InputTextStyle};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::modal::ModalSubmitInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Editor actions that are answered with a dialog instead of being acknowledged straight away,
+/// since a dialog can only be shown in response to an interaction that hasn't been acknowledged
+pub const DIALOG_ACTIONS: &[&str] = &["decline_reason"];
+
+/// A button press or menu selection made in a response editor
+pub struct EditorAction {
+    pub custom_id: String,
+    pub values: Vec<String>,
+}
+
+/// Interaction operations used to drive a response editor, so that the editing flow in
+/// [`Scheduler::get_response`](crate::scheduler::Scheduler::get_response) doesn't depend on a
+/// live [`MessageComponentInteraction`]
+#[async_trait]
+pub trait ResponseSession: Send {
+    fn user_id(&self) -> UserId;
+
+    /// Guild the interaction came from, or `None` for DMs
+    fn guild_id(&self) -> Option<GuildId>;
+
+    /// Whether the user has `role` in the guild the interaction came from
+    async fn has_role(&self, role: RoleId) -> serenity::Result<bool>;
+
+    /// Sends the initial ephemeral reply to the interaction
+    async fn respond(
+        &self,
+        content: Option<String>,
+        components: CreateComponents,
+    ) -> serenity::Result<()>;
+
+    /// Edits the reply sent by [`respond`](ResponseSession::respond); the content is left
+    /// unchanged if `None`
+    async fn edit(
+        &self,
+        content: Option<String>,
+        components: CreateComponents,
+    ) -> serenity::Result<()>;
+
+    /// Waits for and acknowledges the next action on the reply, returning `None` on timeout
+    async fn next_action(&mut self, timeout: Duration) -> Option<EditorAction>;
+
+    /// Shows a dialog asking for a line of text in response to the last action, which must be
+    /// one of [`DIALOG_ACTIONS`]. Returns the text entered, or `None` if it wasn't submitted in
+    /// time.
+    async fn ask_text(
+        &mut self,
+        title: &str,
+        label: &str,
+        max_length: u64,
+        timeout: Duration,
+    ) -> Option<String>;
+}
+
+/// Discord operations of the response editor that don't go through its interaction, so that
+/// the editor can be run without a gateway connection
+#[async_trait]
+pub trait EditorHost: Sync {
+    /// Display name of `user` in `guild`, or `None` if they can't be found
+    async fn member_name(&self, guild: GuildId, user: UserId) -> Option<String>;
+
+    /// Shows a changed response on the scheduler's messages
+    async fn update_messages(&self, scheduler: &Scheduler);
+
+    /// Connection for editors that wait for DM replies, or `None` if there isn't one
+    fn context(&self) -> Option<&Context>;
+}
+
+#[async_trait]
+impl EditorHost for Context {
+    async fn member_name(&self, guild: GuildId, user: UserId) -> Option<String> {
+        let member = guild.member(self, user).await.ok()?;
+        Some(member.display_name().into_owned())
+    }
+
+    async fn update_messages(&self, scheduler: &Scheduler) {
+        scheduler.update_messages(self).await
+    }
+
+    fn context(&self) -> Option<&Context> {
+        Some(self)
+    }
+}
+
+/// Implements [`ResponseSession`] for a session struct wrapping an interaction, since
+/// component and command interactions have identically-named but unrelated methods
+macro_rules! interaction_session {
+    ($(#[$doc:meta])* $name:ident, $field:ident: $interaction:ty) => {
+        $(#[$doc])*
+        pub struct $name<'a> {
+            ctx: &'a Context,
+            $field: &'a $interaction,
+            message: Option<Message>,
+            /// The last action, if it is waiting to be answered with a dialog
+            pending: Option<Arc<MessageComponentInteraction>>,
+        }
+
+        impl<'a> $name<'a> {
+            pub fn new(ctx: &'a Context, $field: &'a $interaction) -> Self {
+                Self {
+                    ctx,
+                    $field,
+                    message: None,
+                    pending: None,
+                }
+            }
+        }
+
+        #[async_trait]
+        impl ResponseSession for $name<'_> {
+            fn user_id(&self) -> UserId {
+                self.$field.user.id
+            }
+
+            fn guild_id(&self) -> Option<GuildId> {
+                self.$field.guild_id
+            }
+
+            async fn has_role(&self, role: RoleId) -> serenity::Result<bool> {
+                match self.$field.guild_id {
+                    Some(guild) => self.$field.user.has_role(self.ctx, guild, role).await,
+                    None => Ok(false),
+                }
+            }
+
+            async fn respond(
+                &self,
+                content: Option<String>,
+                components: CreateComponents,
+            ) -> serenity::Result<()> {
+                self.$field
+                    .create_interaction_response(self.ctx, |r| {
+                        r.kind(InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|m| {
+                                if let Some(content) = content {
+                                    m.content(content);
+                                }
+                                m.ephemeral(true).set_components(components)
+                            })
+                    })
+                    .await
+            }
+
+            async fn edit(
+                &self,
+                content: Option<String>,
+                components: CreateComponents,
+            ) -> serenity::Result<()> {
+                self.$field
+                    .edit_original_interaction_response(self.ctx, |m| {
+                        if let Some(content) = content {
+                            m.content(content);
+                        }
+                        m.components(|c| {
+                            *c = components;
+                            c
+                        })
+                    })
+                    .await
+                    .map(|_| ())
+            }
+
+            async fn next_action(&mut self, timeout: Duration) -> Option<EditorAction> {
+                if self.message.is_none() {
+                    // Treated like a timeout, since the editor can't continue without it
+                    match self.$field.get_interaction_response(self.ctx).await {
+                        Ok(message) => self.message = Some(message),
+                        Err(e) => {
+                            error!("Cannot get response message: {}", e);
+                            return None;
+                        }
+                    }
+                }
+                let interaction = self
+                    .message
+                    .as_ref()
+                    .unwrap()
+                    .await_component_interaction(self.ctx)
+                    .timeout(timeout)
+                    .await?;
+                let action = EditorAction {
+                    custom_id: interaction.data.custom_id.clone(),
+                    values: interaction.data.values.clone(),
+                };
+                if DIALOG_ACTIONS.contains(&action.custom_id.as_str()) {
+                    self.pending = Some(interaction);
+                    return Some(action);
+                }
+                self.pending = None;
+                // The action can still be handled if acknowledging it fails, though the user will see
+                // an error on it
+                if let Err(e) = interaction.defer(self.ctx).await {
+                    warn!("Cannot respond to button: {}", e);
+                }
+                Some(action)
+            }
+
+            async fn ask_text(
+                &mut self,
+                title: &str,
+                label: &str,
+                max_length: u64,
+                timeout: Duration,
+            ) -> Option<String> {
+                let interaction = self.pending.take()?;
+                let shown = interaction
+                    .create_interaction_response(self.ctx, |r| {
+                        r.kind(InteractionResponseType::Modal)
+                            .interaction_response_data(|d| {
+                                d.custom_id("ask_text").title(title).components(|c| {
+                                    c.create_action_row(|ar| {
+                                        ar.create_input_text(|t| {
+                                            t.custom_id("text")
+                                                .label(label)
+                                                .style(InputTextStyle::Short)
+                                                .max_length(max_length)
+                                                .required(false)
+                                        })
+                                    })
+                                })
+                            })
+                    })
+                    .await;
+                if let Err(e) = shown {
+                    error!("Cannot show dialog: {}", e);
+                    return None;
+                }
+                let modal = interaction
+                    .message
+                    .await_modal_interaction(self.ctx)
+                    .author_id(interaction.user.id)
+                    .timeout(timeout)
+                    .await?;
+                if let Err(e) = modal
+                    .create_interaction_response(self.ctx, |r| {
+                        r.kind(InteractionResponseType::DeferredUpdateMessage)
+                    })
+                    .await
+                {
+                    warn!("Cannot respond to dialog: {}", e);
+                }
+                modal
+                    .data
+                    .components
+                    .iter()
+                    .flat_map(|row| row.components.iter())
+                    .find_map(|c| match c {
+                        ActionRowComponent::InputText(t) => Some(t.value.trim().to_owned()),
+                        _ => None,
+                    })
+            }
+        }
+    };
+}
+
+interaction_session!(
+    /// [`ResponseSession`] backed by a component interaction on a scheduler message
+    ComponentSession,
+    component: MessageComponentInteraction
+);
+
+interaction_session!(
+    /// [`ResponseSession`] backed by the submission of a dialog shown before the editor
+    ModalSession,
+    modal: ModalSubmitInteraction
+);
+
+interaction_session!(
+    /// [`ResponseSession`] backed by a slash command interaction
+    CommandSession,
+    command: ApplicationCommandInteraction
+);
+
+/// [`ResponseSession`] that plays back a fixed list of actions instead of waiting for a user,
+/// recording everything the editor shows, for exercising the editor with `/schedule dev_editor`
+/// and in tests
+#[cfg(any(test, feature = "dev-tools"))]
+pub struct ScriptedSession {
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+    actions: std::collections::VecDeque<EditorAction>,
+    /// Each reply, edit and action, in order
+    transcript: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(any(test, feature = "dev-tools"))]
+impl ScriptedSession {
+    pub fn new(user_id: UserId, guild_id: Option<GuildId>, actions: Vec<EditorAction>) -> Self {
+        Self {
+            user_id,
+            guild_id,
+            actions: actions.into(),
+            transcript: Default::default(),
+        }
+    }
+
+    pub fn into_transcript(self) -> Vec<String> {
+        self.transcript.into_inner().unwrap()
+    }
+
+    fn record(&self, kind: &str, content: Option<String>) {
+        let content = content.unwrap_or_else(|| "(content unchanged)".to_owned());
+        self.transcript
+            .lock()
+            .unwrap()
+            .push(format!("{}: {}", kind, content));
+    }
+}
+
+#[cfg(any(test, feature = "dev-tools"))]
+#[async_trait]
+impl ResponseSession for ScriptedSession {
+    fn user_id(&self) -> UserId {
+        self.user_id
+    }
+
+    fn guild_id(&self) -> Option<GuildId> {
+        self.guild_id
+    }
+
+    // The script is run by a developer, who is let through any group role
+    async fn has_role(&self, _role: RoleId) -> serenity::Result<bool> {
+        Ok(true)
+    }
+
+    async fn respond(
+        &self,
+        content: Option<String>,
+        _components: CreateComponents,
+    ) -> serenity::Result<()> {
+        self.record("reply", content);
+        Ok(())
+    }
+
+    async fn edit(
+        &self,
+        content: Option<String>,
+        _components: CreateComponents,
+    ) -> serenity::Result<()> {
+        self.record("edit", content);
+        Ok(())
+    }
+
+    /// Returns `None` once the script has run out, which the editor treats as a timeout
+    async fn next_action(&mut self, _timeout: Duration) -> Option<EditorAction> {
+        let action = self.actions.pop_front()?;
+        let values = format!("{:?}", action.values);
+        self.record("action", Some(format!("{} {}", action.custom_id, values)));
+        Some(action)
+    }
+
+    async fn ask_text(
+        &mut self,
+        _title: &str,
+        _label: &str,
+        _max_length: u64,
+        _timeout: Duration,
+    ) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::Weekday;
+
+/// Translatable text shown on scheduler messages and in the response editor
+#[derive(Clone, Copy)]
+pub enum Text {
+    AddResponse,
+    SetTimes,
+    ShowDetails,
+    FinalResults,
+    ResponsesLocked,
+    ClosedToNew,
+    Responded,
+    Results,
+    ResultsContinued,
+    AllWeekdays,
+    FilterWeekdays,
+    ApplyWeekday,
+    SelectAll,
+    ClearAll,
+    Flexible,
+    CopyResponse,
+    Submit,
+    Decline,
+    DeclineWithReason,
+    Condition,
+    DeleteResponse,
+    ResponseSubmitted,
+    ResponseDeleted,
+    ResponseTimedOut,
+    ResponseCancelled,
+    ResponsesUnlocked,
+    Goal,
+    /// Follows the number of available responders out of the target
+    GoalReached,
+    TowardGoal,
+    Leading,
+    NoResponsesYet,
+    NoDatesLeft,
+    /// Follows an error saving a response
+    SubmitAgain,
+    /// Followed by a weekday in the menu for selecting every date on a weekday
+    AvailableEvery,
+    NotOwner,
+    Closed,
+    Locked,
+    Full,
+    ClosedToNewError,
+    Superseded,
+    TooFast,
+    PinLimit,
+    DiscordFailed,
+    CannotSave,
+}
+
+impl Text {
+    /// Text in the given Discord locale (e.g. `fr`), falling back to English when the locale or
+    /// this text isn't translated
+    pub fn get(self, locale: Option<&str>) -> &'static str {
+        let translated = match locale {
+            Some("fr") => french(self),
+            _ => None,
+        };
+        translated.unwrap_or_else(|| english(self))
+    }
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::AddResponse => "Add response",
+        Text::SetTimes => "Set times",
+        Text::ShowDetails => "Show details",
+        Text::FinalResults => "Final results",
+        Text::ResponsesLocked => "Responses locked",
+        Text::ClosedToNew => "Closed to new responders",
+        Text::Responded => "Responded",
+        Text::Results => "Results",
+        Text::ResultsContinued => "Results (cont.)",
+        Text::AllWeekdays => "All weekdays",
+        Text::FilterWeekdays => "Filter dates by weekday",
+        Text::ApplyWeekday => "Select every date on a weekday",
+        Text::SelectAll => "Select all",
+        Text::ClearAll => "Clear all",
+        Text::Flexible => "I'm flexible (any date works)",
+        Text::CopyResponse => "Copy a response",
+        Text::Submit => "Submit",
+        Text::Decline => "I can't make any of these",
+        Text::DeclineWithReason => "Decline with a reason",
+        Text::Condition => "Only if someone else comes",
+        Text::DeleteResponse => "Delete response",
+        Text::ResponseSubmitted => "Response submitted",
+        Text::ResponseDeleted => "Response deleted",
+        Text::ResponseTimedOut => "Response timed out",
+        Text::ResponseCancelled => "Response cancelled",
+        Text::ResponsesUnlocked => "Responses unlocked",
+        Text::Goal => "Goal",
+        Text::GoalReached => "available 🎉 Goal reached!",
+        Text::TowardGoal => "available toward the goal",
+        Text::Leading => "Leading",
+        Text::NoResponsesYet => "No responses yet",
+        Text::NoDatesLeft => "No dates are left to respond to",
+        Text::SubmitAgain => "submit again to retry",
+        Text::AvailableEvery => "Available every",
+        Text::NotOwner => "Only the scheduler owner may do that",
+        Text::Closed => "Scheduler is closed",
+        Text::Locked => "Responses are locked",
+        Text::Full => "This poll is full",
+        Text::ClosedToNewError => "This poll is closed to new responders",
+        Text::Superseded => "This session was superseded by a newer one",
+        Text::TooFast => "You're doing that too fast",
+        Text::PinLimit => "This channel already has the maximum number of pinned messages",
+        Text::DiscordFailed => "Discord request failed",
+        Text::CannotSave => "Cannot save scheduler",
+    }
+}
+
+fn french(text: Text) -> Option<&'static str> {
+    Some(match text {
+        Text::AddResponse => "Ajouter une réponse",
+        Text::SetTimes => "Indiquer les horaires",
+        Text::ShowDetails => "Afficher les détails",
+        Text::FinalResults => "Résultats définitifs",
+        Text::ResponsesLocked => "Réponses verrouillées",
+        Text::ClosedToNew => "Fermé aux nouveaux participants",
+        Text::Responded => "Réponses",
+        Text::Results => "Résultats",
+        Text::ResultsContinued => "Résultats (suite)",
+        Text::AllWeekdays => "Tous les jours",
+        Text::FilterWeekdays => "Filtrer les dates par jour de la semaine",
+        Text::ApplyWeekday => "Sélectionner toutes les dates d'un jour de la semaine",
+        Text::SelectAll => "Tout sélectionner",
+        Text::ClearAll => "Tout effacer",
+        Text::Flexible => "Je suis flexible (toute date convient)",
+        Text::CopyResponse => "Copier une réponse",
+        Text::Submit => "Envoyer",
+        Text::Decline => "Aucune date ne me convient",
+        Text::DeclineWithReason => "Refuser en donnant une raison",
+        Text::Condition => "Seulement si quelqu'un d'autre vient",
+        Text::DeleteResponse => "Supprimer la réponse",
+        Text::ResponseSubmitted => "Réponse envoyée",
+        Text::ResponseDeleted => "Réponse supprimée",
+        Text::ResponseTimedOut => "Délai de réponse expiré",
+        Text::ResponseCancelled => "Réponse annulée",
+        Text::ResponsesUnlocked => "Réponses déverrouillées",
+        Text::Goal => "Objectif",
+        Text::GoalReached => "disponibles 🎉 Objectif atteint !",
+        Text::TowardGoal => "disponibles pour atteindre l'objectif",
+        Text::Leading => "En tête",
+        Text::NoResponsesYet => "Pas encore de réponses",
+        Text::NoDatesLeft => "Il ne reste aucune date à laquelle répondre",
+        Text::SubmitAgain => "envoyez à nouveau pour réessayer",
+        Text::AvailableEvery => "Disponible chaque",
+        Text::NotOwner => "Seul le créateur du sondage peut faire cela",
+        Text::Closed => "Le sondage est fermé",
+        Text::Locked => "Les réponses sont verrouillées",
+        Text::Full => "Ce sondage est complet",
+        Text::ClosedToNewError => "Ce sondage est fermé aux nouveaux participants",
+        Text::Superseded => "Cette session a été remplacée par une plus récente",
+        Text::TooFast => "Vous allez trop vite",
+        Text::PinLimit => "Ce salon a déjà le nombre maximal de messages épinglés",
+        Text::DiscordFailed => "La requête Discord a échoué",
+        Text::CannotSave => "Impossible d'enregistrer le sondage",
+    })
+}
+
+/// Name of a weekday in the given Discord locale, abbreviated in English
+pub fn weekday_name(day: Weekday, locale: Option<&str>) -> &'static str {
+    match locale {
+        Some("fr") => match day {
+            Weekday::Mon => "lundi",
+            Weekday::Tue => "mardi",
+            Weekday::Wed => "mercredi",
+            Weekday::Thu => "jeudi",
+            Weekday::Fri => "vendredi",
+            Weekday::Sat => "samedi",
+            Weekday::Sun => "dimanche",
+        },
+        _ => match day {
+            Weekday::Mon => "Mon",
+            Weekday::Tue => "Tue",
+            Weekday::Wed => "Wed",
+            Weekday::Thu => "Thu",
+            Weekday::Fri => "Fri",
+            Weekday::Sat => "Sat",
+            Weekday::Sun => "Sun",
+        },
+    }
+}